@@ -47,17 +47,32 @@ fn block_builders() {
     // Headers
     h1("Title");
     h2("Subtitle"); // ... through h6
+    h2("Usage").with_id("usage"); // stable anchor for cross-references
 
     // Content
     p("Paragraph text");
     code_block("rust", "code here");
+    code_block("rust", "let x = 1 / 0;") // ```rust title="main.rs" showLineNumbers=41 {41}
+        .with_title("main.rs")
+        .with_start_line(41)
+        .with_highlighted([41]);
     quote(p("Quoted text"));
+    warning(p("Disk almost full")); // also note, tip, important and danger
+    danger(p("Deletes every branch")).with_title("Before you run this");
+    details("CI log", code_block((), "...")); // collapsed; .with_open(true) to expand
     hr(); // horizontal rule
 
     // Lists
     ul([p("Item 1"), p("Item 2")]); // unordered
     ol([p("Item 1"), p("Item 2")]); // ordered
     task_list([(true, p("Done")), (false, p("Todo"))]);
+    dl((
+        (code("--verbose"), "Print every step."),
+        (
+            code("--config <FILE>"),
+            block((p("Read settings from FILE."), ul(["TOML", "JSON"]))),
+        ),
+    ));
 
     // Tables
     table(("Col1", "Col2"), [("Row1", "Data")]);
@@ -69,6 +84,15 @@ fn block_builders() {
         ),
         [("A", "B", "C")],
     );
+    table(
+        ("Region", cell("Sales").with_colspan(2)), // spanned positions are left empty in Markdown
+        [
+            vec![cell("North").with_rowspan(2), cell("Q1"), cell("10")],
+            vec![cell("Q2"), cell(("12 ", italic("est.")))],
+        ],
+    )
+    .with_footer([(cell("Total").with_colspan(2), "22")])
+    .with_caption("Sales by region"); // caption, written as `Table: ...` in Markdown
 }
 
 fn inline_builders() {
@@ -80,6 +104,11 @@ fn inline_builders() {
     strikethrough("struck text");
     code("inline code");
     link("text", "https://example.com");
+    xref("see usage", "usage"); // link to a heading anchor
+
+    // Footnotes return a reference and a definition, numbered when rendered
+    let (cite, source) = footnote("RFC 9110, section 15.");
+    block((p(("Status codes are standardized", cite, ".")), source));
 }
 
 fn extension_traits() {
@@ -93,19 +122,20 @@ fn extension_traits() {
 }
 
 fn md_renderer() {
-    use docloom::md::{FenceStyle, ListMarker, Style, doc};
+    use docloom::md::{DefinitionLists, FenceStyle, HeadingIds, ListMarker, Style, doc};
 
-    let style = Style {
-        code_fence: FenceStyle::Tilde,     // ``` or ~~~
-        list_marker: ListMarker::Asterisk, // - or *
-        max_heading: 6,                    // Clamp heading levels
-    };
+    let style = Style::default()
+        .code_fence(FenceStyle::Tilde) // ``` or ~~~
+        .list_marker(ListMarker::Asterisk) // - or *
+        .max_heading(6) // Clamp heading levels
+        .heading_ids(HeadingIds::Attribute) // <a id> anchors (default), {#id} or omitted
+        .definition_lists(DefinitionLists::Colon); // Term / : definition or <dl>
 
     let _content = doc([""]).with_style(style);
 }
 
 fn term_renderer() {
-    use docloom::term::{Style, doc};
+    use docloom::term::{Style, Theme, doc};
 
     let style = Style::plain()
         .unicode_boxes(false)
@@ -121,6 +151,55 @@ fn term_renderer() {
 
     let _plain = doc([""]).with_style(style);
     let _ascii = doc([""]).with_style(Style::ascii());
+
+    let theme: Theme = r##"
+base = "solarized"
+headings = ["#268bd2", "cyan", 33]
+link = "bright-blue"
+quote_border = "┃"
+warning = "#b58900"
+"##
+    .parse()
+    .unwrap();
+
+    let _themed = Style::detect().theme(&theme);
+    let _from_file = Theme::load("theme.toml").map(|theme| Style::default().theme(&theme));
+}
+
+fn html_renderer() {
+    use docloom::highlight::Builtin;
+    use docloom::html::{Style, doc};
+    use std::sync::Arc;
+
+    let style = Style::default()
+        .language_prefix("language-") // Code block class prefix
+        .max_heading(6) // Clamp heading levels
+        .highlighter(Some(Arc::new(Builtin))) // Wrap code tokens in <span class="hl-*">
+        .url_schemes(&["http", "https", "mailto"]); // Other link schemes render as text
+
+    let _content = doc([""]).with_style(style);
+}
+
+fn md_parse() {
+    use docloom::prelude::*;
+    use docloom::{md, term};
+
+    let mut blocks = md::parse("## Changelog\n\n- Added **tables**\n");
+    blocks.push(p("Generated content"));
+
+    let _terminal = term::doc(blocks);
+}
+
+fn table_of_contents() {
+    use docloom::prelude::*;
+    use docloom::{LevelFormat, NumberStyle, Numbering, Toc, TocPosition, md};
+
+    let numbering = Numbering::new()
+        .levels(2, 6)
+        .level(3, LevelFormat::new(NumberStyle::LowerAlpha).suffix(")"));
+    let _markdown = md::doc([h1("Guide"), h2("Install"), h2("Usage")])
+        .with_numbering(numbering)
+        .with_toc(Toc::new().position(TocPosition::AfterTitle).levels(2, 3));
 }
 
 fn tuple_conventions() {
@@ -169,6 +248,9 @@ fn main() {
     extension_traits();
     md_renderer();
     term_renderer();
+    html_renderer();
+    md_parse();
+    table_of_contents();
     tuple_conventions();
     custom_rendering();
 }
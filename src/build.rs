@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{Alignment, Block, Inline};
+use crate::{Alignment, Block, CalloutKind, Cell, CodeMeta, Inline, footnote};
 use itemize::{IntoItems, IntoRows};
 
 /// Wrap multiple blocks into a [`Block::BlockList`].
@@ -13,11 +13,12 @@ pub fn p(value: impl IntoItems<Inline>) -> Block {
     Block::Paragraph(value.into_items().collect())
 }
 
-/// Create a heading block at the provided level.
+/// Create a heading block at the provided level, clamped to 1 to 6.
 pub fn h(level: u8, value: impl IntoItems<Inline>) -> Block {
     Block::Heading {
-        level,
+        level: level.clamp(1, 6),
         content: value.into_items().collect(),
+        id: None,
     }
 }
 
@@ -51,11 +52,122 @@ pub fn h6(value: impl IntoItems<Inline>) -> Block {
     h(6, value)
 }
 
+impl Block {
+    /// Give a heading an explicit anchor id that stays stable when its text
+    /// changes.
+    ///
+    /// # Panics
+    /// In debug builds, when called on any other block.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        match &mut self {
+            Block::Heading { id: slot, .. } => *slot = Some(id.into()),
+            block => debug_assert!(false, "anchor id set on {}", block.kind()),
+        }
+        self
+    }
+
+    /// Caption a code block, for example with its file name, or replace the
+    /// title of a callout.
+    ///
+    /// # Panics
+    /// In debug builds, when called on any other block.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        if let Block::Callout { title: slot, .. } = &mut self {
+            *slot = Some(title.into());
+            return self;
+        }
+        self.with_code_meta(|meta| meta.title = Some(title.into()))
+    }
+
+    /// Caption a table.
+    ///
+    /// # Panics
+    /// In debug builds, when called on any other block.
+    pub fn with_caption(mut self, caption: impl Into<String>) -> Self {
+        match &mut self {
+            Block::Table { caption: slot, .. } => *slot = Some(caption.into()),
+            block => debug_assert!(false, "caption set on {}", block.kind()),
+        }
+        self
+    }
+
+    /// Add footer rows, such as totals, to a table.
+    ///
+    /// # Panics
+    /// In debug builds, when called on any other block.
+    pub fn with_footer(mut self, rows: impl IntoRows<Cell>) -> Self {
+        match &mut self {
+            Block::Table { footer, .. } => {
+                footer.extend(rows.into_rows().map(|row| row.collect::<Vec<_>>()))
+            }
+            block => debug_assert!(false, "footer rows added to {}", block.kind()),
+        }
+        self
+    }
+
+    /// Number the lines of a code block from `start_line`.
+    ///
+    /// # Panics
+    /// In debug builds, when called on any other block.
+    pub fn with_start_line(self, start_line: usize) -> Self {
+        self.with_code_meta(|meta| meta.start_line = Some(start_line))
+    }
+
+    /// Emphasize lines of a code block, numbered as displayed.
+    ///
+    /// # Panics
+    /// In debug builds, when called on any other block.
+    pub fn with_highlighted(self, lines: impl IntoIterator<Item = usize>) -> Self {
+        self.with_code_meta(|meta| meta.highlighted.extend(lines))
+    }
+
+    /// Expand a details block when `open` is set.
+    ///
+    /// # Panics
+    /// In debug builds, when called on any other block.
+    pub fn with_open(mut self, open: bool) -> Self {
+        match &mut self {
+            Block::Details { open: slot, .. } => *slot = open,
+            block => debug_assert!(false, "open state set on {}", block.kind()),
+        }
+        self
+    }
+
+    /// The kind of block, for messages about misplaced builder calls.
+    fn kind(&self) -> &'static str {
+        match self {
+            Block::Paragraph(_) => "a paragraph",
+            Block::Heading { .. } => "a heading",
+            Block::CodeBlock { .. } => "a code block",
+            Block::Blockquote(_) => "a blockquote",
+            Block::List { .. } => "a list",
+            Block::TaskList { .. } => "a task list",
+            Block::Table { .. } => "a table",
+            Block::Image { .. } => "an image",
+            Block::HorizontalRule => "a horizontal rule",
+            Block::BlockList(_) => "a block list",
+            Block::FootnoteDefinition { .. } => "a footnote definition",
+            Block::Callout { .. } => "a callout",
+            Block::DefinitionList(_) => "a definition list",
+            Block::Details { .. } => "a details block",
+        }
+    }
+
+    fn with_code_meta(mut self, update: impl FnOnce(&mut CodeMeta)) -> Self {
+        match &mut self {
+            Block::CodeBlock { meta, .. } => update(meta),
+            block => debug_assert!(false, "code block metadata set on {}", block.kind()),
+        }
+        self
+    }
+}
+
 /// Create a fenced code block with an optional language hint.
 pub fn code_block(language: impl Into<OptString>, value: impl Into<String>) -> Block {
     Block::CodeBlock {
         language: language.into().0,
         content: value.into(),
+        meta: CodeMeta::default(),
     }
 }
 
@@ -98,24 +210,65 @@ pub fn task_list(items: impl IntoItems<TaskItem>) -> Block {
 }
 
 #[derive(itemize::IntoItems)]
-#[items_from(types(Inline), tuples(12), collections(vec, slice, array))]
-pub struct Align(Alignment, Inline);
+#[items_from(tuples(12), collections(vec, slice, array))]
+pub struct Definition(Vec<Inline>, Vec<Block>);
+
+impl<T, U> From<(T, U)> for Definition
+where
+    T: IntoItems<Inline>,
+    U: Into<Block>,
+{
+    fn from(value: (T, U)) -> Self {
+        // A block list holds several blocks of one definition
+        let definition = match value.1.into() {
+            Block::BlockList(blocks) => blocks,
+            block => vec![block],
+        };
+        Definition(value.0.into_items().collect(), definition)
+    }
+}
+
+/// Create a definition list from `(term, definition)` pairs.
+///
+/// Use [`block`] to give a term a definition of several blocks.
+///
+/// # Examples
+/// ```rust
+/// use docloom::md::doc;
+/// use docloom::prelude::*;
+///
+/// let flags = dl((
+///     (code("--verbose"), "Print every step."),
+///     (code("--config <FILE>"), block(("Read settings from FILE.", ul(["TOML", "JSON"])))),
+/// ));
+/// assert_eq!(
+///     doc(flags).to_string(),
+///     "`--verbose`\n:   Print every step.\n\n`--config <FILE>`\n:   Read settings from FILE.\n    - TOML\n    - JSON\n\n"
+/// );
+/// ```
+pub fn dl(items: impl IntoItems<Definition>) -> Block {
+    Block::DefinitionList(items.into_items().map(|item| (item.0, item.1)).collect())
+}
+
+#[derive(itemize::IntoItems)]
+#[items_from(types(Cell, Inline), tuples(12), collections(vec, slice, array))]
+pub struct Align(Alignment, Cell);
 
 impl Align {
-    pub fn left(inline: impl Into<Inline>) -> Self {
-        Align(Alignment::Left, inline.into())
+    pub fn left(cell: impl Into<Cell>) -> Self {
+        Align(Alignment::Left, cell.into())
     }
-    pub fn center(inline: impl Into<Inline>) -> Self {
-        Align(Alignment::Center, inline.into())
+    pub fn center(cell: impl Into<Cell>) -> Self {
+        Align(Alignment::Center, cell.into())
     }
-    pub fn right(inline: impl Into<Inline>) -> Self {
-        Align(Alignment::Right, inline.into())
+    pub fn right(cell: impl Into<Cell>) -> Self {
+        Align(Alignment::Right, cell.into())
     }
 }
 
 impl<T> From<T> for Align
 where
-    T: Into<Inline>,
+    T: Into<Cell>,
 {
     fn from(value: T) -> Self {
         Align::left(value)
@@ -123,17 +276,73 @@ where
 }
 
 /// Create a table with left-aligned columns.
-pub fn table(headers: impl IntoItems<Align>, rows: impl IntoRows<Inline>) -> Block {
+///
+/// Use [`cell`] for cells with several inline elements or spans, and
+/// [`Block::with_footer`] and [`Block::with_caption`] to add footer rows and
+/// a caption.
+///
+/// # Examples
+/// ```rust
+/// use docloom::md::doc;
+/// use docloom::prelude::*;
+///
+/// let report = table(
+///     ("Check", Align::right("Time")),
+///     ((cell((bold("lint"), " ", code("clippy"))), "4s"), ("test", "12s")),
+/// )
+/// .with_footer([("Total", "16s")]);
+/// assert_eq!(
+///     doc(report).to_string(),
+///     "| Check             | Time |\n\
+///      | :---------------- | ---: |\n\
+///      | **lint** `clippy` | 4s   |\n\
+///      | test              | 12s  |\n\
+///      | Total             | 16s  |\n\n"
+/// );
+/// ```
+pub fn table(headers: impl IntoItems<Align>, rows: impl IntoRows<Cell>) -> Block {
     let mut columns = Vec::new();
     let mut alignments = Vec::new();
     for header in headers.into_items() {
+        // A header spanning several columns aligns each of them
+        alignments.extend(std::iter::repeat_n(header.0, header.1.colspan.max(1)));
         columns.push(header.1);
-        alignments.push(header.0);
     }
     Block::Table {
         headers: columns,
         rows: rows.into_rows().map(|row| row.collect()).collect(),
         alignments,
+        footer: Vec::new(),
+        caption: None,
+    }
+}
+
+/// Create a table cell from inline elements. Empty text is left out, so
+/// `cell("")` is an empty cell.
+pub fn cell(value: impl IntoItems<Inline>) -> Cell {
+    Cell {
+        content: value
+            .into_items()
+            .filter(|inline| !matches!(inline, Inline::Text(text) if text.is_empty()))
+            .collect(),
+        colspan: 1,
+        rowspan: 1,
+    }
+}
+
+impl Cell {
+    /// Make the cell cover `columns` columns, starting with its own. A span
+    /// of 0 counts as 1.
+    pub fn with_colspan(mut self, columns: usize) -> Self {
+        self.colspan = columns.max(1);
+        self
+    }
+
+    /// Make the cell cover `rows` rows, starting with its own. The rows
+    /// below leave out the cells it covers. A span of 0 counts as 1.
+    pub fn with_rowspan(mut self, rows: usize) -> Self {
+        self.rowspan = rows.max(1);
+        self
     }
 }
 
@@ -147,6 +356,62 @@ pub fn quote(value: impl IntoItems<Block>) -> Block {
     Block::Blockquote(value.into_items().collect())
 }
 
+/// Create a callout of `kind` from nested blocks.
+pub fn callout(kind: CalloutKind, value: impl IntoItems<Block>) -> Block {
+    Block::Callout {
+        kind,
+        title: None,
+        content: value.into_items().collect(),
+    }
+}
+
+/// Create a note callout.
+pub fn note(value: impl IntoItems<Block>) -> Block {
+    callout(CalloutKind::Note, value)
+}
+
+/// Create a tip callout.
+pub fn tip(value: impl IntoItems<Block>) -> Block {
+    callout(CalloutKind::Tip, value)
+}
+
+/// Create an important callout.
+pub fn important(value: impl IntoItems<Block>) -> Block {
+    callout(CalloutKind::Important, value)
+}
+
+/// Create a warning callout.
+pub fn warning(value: impl IntoItems<Block>) -> Block {
+    callout(CalloutKind::Warning, value)
+}
+
+/// Create a danger callout.
+pub fn danger(value: impl IntoItems<Block>) -> Block {
+    callout(CalloutKind::Danger, value)
+}
+
+/// Create a collapsed section that shows `summary` and hides the nested
+/// blocks until expanded.
+///
+/// # Examples
+/// ```rust
+/// use docloom::md::doc;
+/// use docloom::prelude::*;
+///
+/// let log = details("Build log", code_block((), "Compiling docloom"));
+/// assert_eq!(
+///     doc(log).to_string(),
+///     "<details>\n<summary>Build log</summary>\n\n```\nCompiling docloom\n```\n\n</details>\n\n"
+/// );
+/// ```
+pub fn details(summary: impl IntoItems<Inline>, value: impl IntoItems<Block>) -> Block {
+    Block::Details {
+        summary: summary.into_items().collect(),
+        content: value.into_items().collect(),
+        open: false,
+    }
+}
+
 /// Create a text inline node.
 pub fn text(value: impl fmt::Display) -> Inline {
     Inline::Text(value.to_string())
@@ -180,6 +445,45 @@ pub fn link(text: impl IntoItems<Inline>, url: impl Into<String>) -> Inline {
     }
 }
 
+/// Create a link to the heading with anchor `id` in the same document.
+///
+/// Pair it with [`Block::with_id`] so the reference survives heading renames.
+pub fn xref(text: impl IntoItems<Inline>, id: impl fmt::Display) -> Inline {
+    link(text, format!("#{id}"))
+}
+
+/// Create a footnote with a paragraph of inline content, returning the
+/// reference to place in the text and the definition to add anywhere in the
+/// document.
+///
+/// The label is derived from the content, so repeating a note reuses its
+/// footnote. Renderers number footnotes in order of reference, and documents
+/// list the definitions after the last block.
+///
+/// # Examples
+/// ```rust
+/// use docloom::md::doc;
+/// use docloom::prelude::*;
+///
+/// let (rfc, source) = footnote("RFC 9110, section 15.");
+/// let markdown = doc([p(("Status codes are standardized", rfc, ".")), source]).to_string();
+/// assert_eq!(
+///     markdown,
+///     "Status codes are standardized[^1].\n\n[^1]: RFC 9110, section 15.\n\n"
+/// );
+/// ```
+pub fn footnote(value: impl IntoItems<Inline>) -> (Inline, Block) {
+    let inlines: Vec<Inline> = value.into_items().collect();
+    let label = footnote::label(&inlines);
+    (
+        Inline::FootnoteRef(label.clone()),
+        Block::FootnoteDefinition {
+            label,
+            content: vec![Block::Paragraph(inlines)],
+        },
+    )
+}
+
 /// Extension trait for creating block elements with method syntax.
 pub trait BlockExt: Sized + IntoItems<Inline> {
     /// Convert the value into a level-one heading.
@@ -0,0 +1,208 @@
+//! Footnote numbering for renderers that list notes after the document.
+
+use std::collections::HashMap;
+
+use crate::{
+    Block, Fold, Inline, Visitor, fold_block_children, fold_inline_children, walk_block,
+    walk_inline,
+};
+
+/// Move the footnote definitions in `blocks` to a separate list of notes.
+///
+/// Footnotes are numbered `1`, `2`, ... in order of their first reference,
+/// and both references and notes are relabelled with those numbers. As in
+/// GFM, definitions that are never referenced are dropped, and references
+/// without a definition keep their label. Returns `None` when `blocks` has no
+/// footnote definitions.
+pub(crate) fn collect(blocks: &[Block]) -> Option<(Vec<Block>, Vec<Block>)> {
+    let mut scan = Scan::default();
+    blocks.iter().for_each(|b| scan.visit_block(b));
+    if scan.definitions.is_empty() {
+        return None;
+    }
+
+    let labels: Vec<String> = scan
+        .references
+        .into_iter()
+        .filter(|label| scan.definitions.contains_key(label))
+        .collect();
+    let mut relabel = Relabel(
+        labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| (label.clone(), (i + 1).to_string()))
+            .collect(),
+    );
+    let body = blocks
+        .iter()
+        .filter_map(|b| relabel.fold_block(b.clone()))
+        .collect();
+    let notes = labels
+        .iter()
+        .map(|label| {
+            let content = scan.definitions.remove(label).unwrap_or_default();
+            Block::FootnoteDefinition {
+                label: relabel.0[label].clone(),
+                content: content
+                    .into_iter()
+                    .filter_map(|b| relabel.fold_block(b))
+                    .collect(),
+            }
+        })
+        .collect();
+    Some((body, notes))
+}
+
+/// A label for a note with `content`, the same for equal content in every
+/// release.
+///
+/// The content is written in a length-prefixed form of its own, so the label
+/// never depends on `Debug` output, and hashed with 64-bit FNV-1a.
+pub(crate) fn label(content: &[Inline]) -> String {
+    fn field(tag: char, text: &str, out: &mut String) {
+        out.push(tag);
+        out.push_str(&text.len().to_string());
+        out.push(':');
+        out.push_str(text);
+    }
+
+    fn encode(inlines: &[Inline], out: &mut String) {
+        for inline in inlines {
+            match inline {
+                Inline::Text(text) => field('t', text, out),
+                Inline::Code(text) => field('c', text, out),
+                Inline::Bold(inner) | Inline::Italic(inner) | Inline::Strikethrough(inner) => {
+                    out.push(match inline {
+                        Inline::Bold(_) => 'b',
+                        Inline::Italic(_) => 'i',
+                        _ => 's',
+                    });
+                    out.push('(');
+                    encode(inner, out);
+                    out.push(')');
+                }
+                Inline::Link { text, url } => {
+                    field('l', url, out);
+                    out.push('(');
+                    encode(text, out);
+                    out.push(')');
+                }
+                Inline::Image { alt, url } => {
+                    field('m', url, out);
+                    field(':', alt, out);
+                }
+                Inline::LineBreak => out.push('n'),
+                Inline::FootnoteRef(label) => field('f', label, out),
+            }
+        }
+    }
+
+    let mut key = String::new();
+    encode(content, &mut key);
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Footnote references in document order and the first definition of each
+/// label.
+#[derive(Default)]
+struct Scan {
+    references: Vec<String>,
+    definitions: HashMap<String, Vec<Block>>,
+}
+
+impl Visitor for Scan {
+    fn visit_block(&mut self, block: &Block) {
+        match block {
+            // References inside a note do not decide the numbering
+            Block::FootnoteDefinition { label, content } => {
+                self.definitions
+                    .entry(label.clone())
+                    .or_insert_with(|| content.clone());
+            }
+            block => walk_block(self, block),
+        }
+    }
+
+    fn visit_inline(&mut self, inline: &Inline) {
+        match inline {
+            Inline::FootnoteRef(label) if !self.references.contains(label) => {
+                self.references.push(label.clone());
+            }
+            inline => walk_inline(self, inline),
+        }
+    }
+}
+
+/// Removes definitions and renames references to their footnote number.
+struct Relabel(HashMap<String, String>);
+
+impl Fold for Relabel {
+    fn fold_block(&mut self, block: Block) -> Option<Block> {
+        match block {
+            Block::FootnoteDefinition { .. } => None,
+            block => fold_block_children(self, block),
+        }
+    }
+
+    fn fold_inline(&mut self, inline: Inline) -> Option<Inline> {
+        match inline {
+            Inline::FootnoteRef(label) => Some(Inline::FootnoteRef(
+                self.0.get(&label).cloned().unwrap_or(label),
+            )),
+            inline => fold_inline_children(self, inline),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_collect_numbers_by_reference() {
+        let note = |label: &str, text: &str| Block::FootnoteDefinition {
+            label: label.into(),
+            content: vec![p(text)],
+        };
+        let blocks = [
+            note("b", "Second"),
+            p(("x", Inline::FootnoteRef("a".into()))),
+            quote(p((
+                Inline::FootnoteRef("b".into()),
+                Inline::FootnoteRef("a".into()),
+                Inline::FootnoteRef("missing".into()),
+            ))),
+            note("a", "First"),
+            note("unused", "Dropped"),
+        ];
+        let (body, notes) = collect(&blocks).unwrap();
+        assert_eq!(
+            body,
+            [
+                p(("x", Inline::FootnoteRef("1".into()))),
+                quote(p((
+                    Inline::FootnoteRef("2".into()),
+                    Inline::FootnoteRef("1".into()),
+                    Inline::FootnoteRef("missing".into()),
+                ))),
+            ]
+        );
+        assert_eq!(notes, [note("1", "First"), note("2", "Second")]);
+
+        assert_eq!(collect(&[p(vec![Inline::FootnoteRef("a".into())])]), None);
+    }
+
+    #[test]
+    fn test_label_is_stable() {
+        // Labels must not change between releases
+        assert_eq!(label(&[text("RFC 9110, section 15.")]), "4b9a2e27797b6523");
+        assert_eq!(label(&[text("ab")]), label(&[text("ab")]));
+        assert_ne!(label(&[text("ab")]), label(&[bold("ab")]));
+        assert_ne!(label(&[text("a"), text("b")]), label(&[text("ab")]));
+        assert_ne!(label(&[link("a", "b")]), label(&[link("b", "a")]));
+    }
+}
@@ -0,0 +1,577 @@
+//! Syntax highlighting for code blocks.
+//!
+//! A [`Highlighter`] splits source code into [`Token`]s that renderers color
+//! or wrap in markup. [`Builtin`] is a small tokenizer for Rust, JSON, TOML,
+//! shell, YAML and diffs; other highlighters can be plugged into the
+//! [`crate::term::Style`] and [`crate::html::Style`] of a document.
+//!
+//! # Examples
+//! ```rust
+//! use docloom::highlight::{Builtin, Highlighter, Token, TokenKind};
+//!
+//! let tokens = Builtin.highlight("json", r#"{"ok": true}"#).unwrap();
+//! assert_eq!(tokens[1], Token::new(TokenKind::Property, r#""ok""#));
+//! assert_eq!(tokens[3], Token::new(TokenKind::Keyword, "true"));
+//! ```
+
+/// What a [`Token`] represents, which decides how it is colored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Identifiers, punctuation and anything else without special meaning.
+    Plain,
+    /// Keywords and built-in literals such as `true` and `null`.
+    Keyword,
+    /// String and character literals.
+    String,
+    /// Numeric literals.
+    Number,
+    /// Comments.
+    Comment,
+    /// Keys of JSON objects, TOML tables and YAML mappings.
+    Property,
+    /// Lines added in a diff.
+    Inserted,
+    /// Lines removed in a diff.
+    Deleted,
+    /// Attributes, macros, variables, section headers and diff hunk headers.
+    Meta,
+}
+
+impl TokenKind {
+    /// Every kind except [`TokenKind::Plain`].
+    pub const HIGHLIGHTED: [TokenKind; 8] = [
+        TokenKind::Keyword,
+        TokenKind::String,
+        TokenKind::Number,
+        TokenKind::Comment,
+        TokenKind::Property,
+        TokenKind::Inserted,
+        TokenKind::Deleted,
+        TokenKind::Meta,
+    ];
+
+    /// Lowercase name of the kind, such as `"keyword"`, used for HTML classes
+    /// and theme keys.
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenKind::Plain => "plain",
+            TokenKind::Keyword => "keyword",
+            TokenKind::String => "string",
+            TokenKind::Number => "number",
+            TokenKind::Comment => "comment",
+            TokenKind::Property => "property",
+            TokenKind::Inserted => "inserted",
+            TokenKind::Deleted => "deleted",
+            TokenKind::Meta => "meta",
+        }
+    }
+}
+
+/// A run of source text of one [`TokenKind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+}
+
+impl<'a> Token<'a> {
+    /// Create a token of `kind` covering `text`.
+    pub fn new(kind: TokenKind, text: &'a str) -> Self {
+        Self { kind, text }
+    }
+}
+
+/// Splits code into tokens for coloring.
+///
+/// The texts of the returned tokens, concatenated, must equal `code`.
+///
+/// # Examples
+/// ```rust
+/// use docloom::highlight::{Highlighter, Token, TokenKind};
+/// use std::sync::Arc;
+///
+/// /// Marks `TODO` lines in plain text files.
+/// struct Todos;
+///
+/// impl Highlighter for Todos {
+///     fn highlight<'a>(&self, language: &str, code: &'a str) -> Option<Vec<Token<'a>>> {
+///         (language == "txt").then(|| {
+///             code.split_inclusive('\n')
+///                 .map(|line| {
+///                     let todo = line.starts_with("TODO");
+///                     let kind = if todo { TokenKind::Meta } else { TokenKind::Plain };
+///                     Token::new(kind, line)
+///                 })
+///                 .collect()
+///         })
+///     }
+/// }
+///
+/// let style = docloom::term::Style::default().highlighter(Some(Arc::new(Todos)));
+/// ```
+pub trait Highlighter: Sync {
+    /// Tokenize `code` written in `language`, or return `None` if the
+    /// language is not supported.
+    fn highlight<'a>(&self, language: &str, code: &'a str) -> Option<Vec<Token<'a>>>;
+}
+
+/// Lightweight built-in highlighter for Rust, JSON, TOML, shell, YAML and
+/// diffs.
+///
+/// It recognizes tokens rather than parsing, so it is fast and never fails,
+/// but may misjudge unusual code.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Builtin;
+
+impl Highlighter for Builtin {
+    fn highlight<'a>(&self, language: &str, code: &'a str) -> Option<Vec<Token<'a>>> {
+        let grammar = match language.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => &RUST,
+            "json" | "jsonc" => &JSON,
+            "toml" => &TOML,
+            "sh" | "bash" | "shell" | "zsh" | "console" => &SHELL,
+            "yaml" | "yml" => &YAML,
+            "diff" | "patch" => return Some(diff(code)),
+            _ => return None,
+        };
+        Some(Scanner::new(grammar, code).run())
+    }
+}
+
+/// How a language marks up the keys of its mappings.
+#[derive(PartialEq)]
+enum Keys {
+    None,
+    /// A string followed by `:`, as in JSON.
+    Json,
+    /// A bare or quoted key at the start of a line followed by `sep`.
+    Line {
+        sep: char,
+    },
+}
+
+/// The lexical rules of a language.
+struct Grammar {
+    keywords: &'static [&'static str],
+    line_comment: Option<&'static str>,
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    keys: Keys,
+    /// `[table]` headers at the start of a line, as in TOML.
+    sections: bool,
+    /// `$NAME` and `${...}` variables, as in shell.
+    variables: bool,
+    /// Rust attributes, macros and lifetimes.
+    rust: bool,
+}
+
+const RUST: Grammar = Grammar {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    keys: Keys::None,
+    sections: false,
+    variables: false,
+    rust: true,
+};
+
+const JSON: Grammar = Grammar {
+    keywords: &["true", "false", "null"],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    keys: Keys::Json,
+    sections: false,
+    variables: false,
+    rust: false,
+};
+
+const TOML: Grammar = Grammar {
+    keywords: &["true", "false"],
+    line_comment: Some("#"),
+    block_comment: None,
+    quotes: &['"', '\''],
+    keys: Keys::Line { sep: '=' },
+    sections: true,
+    variables: false,
+    rust: false,
+};
+
+const SHELL: Grammar = Grammar {
+    keywords: &[
+        "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+        "in", "function", "return", "export", "local", "readonly",
+    ],
+    line_comment: Some("#"),
+    block_comment: None,
+    quotes: &['"', '\''],
+    keys: Keys::None,
+    sections: false,
+    variables: true,
+    rust: false,
+};
+
+const YAML: Grammar = Grammar {
+    keywords: &["true", "false", "null", "yes", "no", "on", "off", "~"],
+    line_comment: Some("#"),
+    block_comment: None,
+    quotes: &['"', '\''],
+    keys: Keys::Line { sep: ':' },
+    sections: false,
+    variables: false,
+    rust: false,
+};
+
+/// Walks code once, emitting a token for each recognized construct.
+struct Scanner<'g, 'a> {
+    grammar: &'g Grammar,
+    code: &'a str,
+    pos: usize,
+    /// Whether only indentation (and YAML list dashes) precede `pos`.
+    line_start: bool,
+    tokens: Vec<Token<'a>>,
+}
+
+impl<'g, 'a> Scanner<'g, 'a> {
+    fn new(grammar: &'g Grammar, code: &'a str) -> Self {
+        Self {
+            grammar,
+            code,
+            pos: 0,
+            line_start: true,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<Token<'a>> {
+        while self.pos < self.code.len() {
+            let rest = &self.code[self.pos..];
+            let (kind, len) = self.next_token(rest);
+            self.line_start = match &rest[..len] {
+                "\n" => true,
+                text if text.trim().is_empty() => self.line_start,
+                "-" if self.grammar.keys == (Keys::Line { sep: ':' }) => self.line_start,
+                _ => false,
+            };
+            self.push(kind, len);
+        }
+        self.tokens
+    }
+
+    fn push(&mut self, kind: TokenKind, len: usize) {
+        let text = &self.code[self.pos..self.pos + len];
+        self.pos += len;
+        match self.tokens.last_mut() {
+            // Merge runs of the same kind, so output stays compact
+            Some(last) if last.kind == kind && kind == TokenKind::Plain => {
+                let start = self.pos - len - last.text.len();
+                last.text = &self.code[start..self.pos];
+            }
+            _ => self.tokens.push(Token::new(kind, text)),
+        }
+    }
+
+    /// Kind and byte length of the token at the start of `rest`.
+    fn next_token(&self, rest: &str) -> (TokenKind, usize) {
+        let grammar = self.grammar;
+        let c = rest.chars().next().unwrap_or(' ');
+        let previous = self.code[..self.pos].chars().next_back();
+
+        if c == '\n' {
+            return (TokenKind::Plain, 1);
+        }
+        if c.is_whitespace() {
+            let len = rest.find(|c: char| !c.is_whitespace() || c == '\n');
+            return (TokenKind::Plain, len.unwrap_or(rest.len()));
+        }
+        if let Some(prefix) = grammar.line_comment
+            // A shell `#` only starts a comment at the start of a word
+            && rest.starts_with(prefix)
+            && (!grammar.variables || previous.is_none_or(char::is_whitespace))
+        {
+            return (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()));
+        }
+        if let Some((open, close)) = grammar.block_comment
+            && rest.starts_with(open)
+        {
+            let len = rest[open.len()..]
+                .find(close)
+                .map_or(rest.len(), |end| open.len() + end + close.len());
+            return (TokenKind::Comment, len);
+        }
+        if self.line_start {
+            if grammar.sections && c == '[' {
+                return (
+                    TokenKind::Meta,
+                    rest.find(']').map_or(rest.len(), |end| {
+                        end + if rest[end..].starts_with("]]") { 2 } else { 1 }
+                    }),
+                );
+            }
+            if let Keys::Line { sep } = grammar.keys
+                && let Some(len) = line_key(rest, sep)
+            {
+                return (TokenKind::Property, len);
+            }
+        }
+        if grammar.rust {
+            if c == '#' && (rest.starts_with("#[") || rest.starts_with("#![")) {
+                return (TokenKind::Meta, bracketed(rest));
+            }
+            // A quote that does not close a character literal is a lifetime
+            if c == '\'' && !is_char_literal(rest) {
+                return (TokenKind::Plain, 1 + ident_len(&rest[1..]));
+            }
+        }
+        if grammar.variables && c == '$' {
+            let len = match rest[1..].chars().next() {
+                Some('{') => rest.find('}').map_or(rest.len(), |end| end + 1),
+                Some(c) if c.is_alphabetic() || c == '_' => 1 + ident_len(&rest[1..]),
+                Some(c) if c.is_ascii_punctuation() || c.is_ascii_digit() => 2,
+                _ => 1,
+            };
+            return (TokenKind::Meta, len);
+        }
+        if grammar.quotes.contains(&c) {
+            let len = string_len(rest, c, c == '"' || grammar.rust);
+            let after = rest[len..].trim_start_matches([' ', '\t']);
+            if grammar.keys == Keys::Json && after.starts_with(':') {
+                return (TokenKind::Property, len);
+            }
+            return (TokenKind::String, len);
+        }
+        if c.is_ascii_digit() {
+            return (TokenKind::Number, number_len(rest));
+        }
+        if c.is_alphabetic() || c == '_' {
+            let len = ident_len(rest);
+            if grammar.rust && rest[len..].starts_with('!') && !rest[len..].starts_with("!=") {
+                return (TokenKind::Meta, len + 1);
+            }
+            let word = &rest[..len];
+            if grammar.keywords.contains(&word) {
+                return (TokenKind::Keyword, len);
+            }
+            return (TokenKind::Plain, len);
+        }
+        if grammar.keywords.contains(&"~") && c == '~' {
+            return (TokenKind::Keyword, 1);
+        }
+        (TokenKind::Plain, c.len_utf8())
+    }
+}
+
+/// Byte length of the identifier at the start of `text`.
+fn ident_len(text: &str) -> usize {
+    text.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len())
+}
+
+/// Byte length of the number at the start of `text`, stopping before `..`.
+fn number_len(text: &str) -> usize {
+    let mut len = 0;
+    for (i, c) in text.char_indices() {
+        let range = c == '.' && text[i + 1..].starts_with('.');
+        if range || !(c.is_alphanumeric() || c == '_' || c == '.') {
+            break;
+        }
+        len = i + c.len_utf8();
+    }
+    len
+}
+
+/// Byte length of the string starting with `quote`, including both quotes.
+///
+/// Backslashes escape the next character only when `escapes` is set, since
+/// single-quoted strings in TOML, YAML and shell are literal.
+fn string_len(text: &str, quote: char, escapes: bool) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if escapes => escaped = true,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+/// Whether `text` starts with a Rust character literal such as `'a'` or `'\n'`.
+fn is_char_literal(text: &str) -> bool {
+    let mut chars = text.chars().skip(1);
+    match chars.next() {
+        Some('\\') => true,
+        Some(_) => chars.next() == Some('\''),
+        None => false,
+    }
+}
+
+/// Byte length of a `#[...]` attribute, including nested brackets.
+fn bracketed(text: &str) -> usize {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' if depth == 1 => return i + 1,
+            ']' => depth -= 1,
+            '\n' => return i,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+/// Byte length of a key at the start of a line that is followed by `sep`.
+fn line_key(text: &str, sep: char) -> Option<usize> {
+    let len = match text.chars().next()? {
+        quote @ ('"' | '\'') => string_len(text, quote, quote == '"'),
+        _ => text
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+            .unwrap_or(text.len()),
+    };
+    let after = text[len..].trim_start_matches([' ', '\t']);
+    let is_key = len > 0
+        && after.starts_with(sep)
+        // YAML needs a space after the colon, so `http://` is not a key
+        && (sep != ':' || after[1..].starts_with([' ', '\t', '\n']) || after.len() == 1);
+    is_key.then_some(len)
+}
+
+/// Tokenize a unified diff line by line.
+fn diff(code: &str) -> Vec<Token<'_>> {
+    code.split_inclusive('\n')
+        .map(|line| {
+            let kind = if line.starts_with("+++") || line.starts_with("---") {
+                TokenKind::Meta
+            } else if line.starts_with('+') {
+                TokenKind::Inserted
+            } else if line.starts_with('-') {
+                TokenKind::Deleted
+            } else if ["@@", "diff ", "index "]
+                .iter()
+                .any(|p| line.starts_with(p))
+            {
+                TokenKind::Meta
+            } else {
+                TokenKind::Plain
+            };
+            Token::new(kind, line)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    /// Highlighted tokens, skipping plain text.
+    fn tokens<'a>(language: &str, code: &'a str) -> Vec<(TokenKind, &'a str)> {
+        let tokens = Builtin.highlight(language, code).unwrap();
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|t| t.text)
+                .collect::<std::string::String>(),
+            code
+        );
+        tokens
+            .into_iter()
+            .filter(|t| t.kind != Plain)
+            .map(|t| (t.kind, t.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust() {
+        let code = "#[derive(Debug)]\nfn main<'a>() {\n    // hi\n    let c = '\\n';\n    println!(\"{}\", 1..2);\n}";
+        assert_eq!(
+            tokens("rust", code),
+            [
+                (Meta, "#[derive(Debug)]"),
+                (Keyword, "fn"),
+                (Comment, "// hi"),
+                (Keyword, "let"),
+                (String, "'\\n'"),
+                (Meta, "println!"),
+                (String, "\"{}\""),
+                (Number, "1"),
+                (Number, "2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_data_formats() {
+        assert_eq!(
+            tokens("json", "{\"a\": [1.5, \"x\", null]}"),
+            [
+                (Property, "\"a\""),
+                (Number, "1.5"),
+                (String, "\"x\""),
+                (Keyword, "null")
+            ]
+        );
+        assert_eq!(
+            tokens(
+                "toml",
+                "[package]\nname = \"docloom\" # crate\nedition.workspace = true"
+            ),
+            [
+                (Meta, "[package]"),
+                (Property, "name"),
+                (String, "\"docloom\""),
+                (Comment, "# crate"),
+                (Property, "edition.workspace"),
+                (Keyword, "true"),
+            ]
+        );
+        assert_eq!(
+            tokens(
+                "yaml",
+                "jobs:\n  - name: test\n    url: http://x\n    on: true"
+            ),
+            [
+                (Property, "jobs"),
+                (Property, "name"),
+                (Property, "url"),
+                (Property, "on"),
+                (Keyword, "true"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_and_diff() {
+        assert_eq!(
+            tokens("sh", "if [ -n \"$HOME\" ]; then echo ${#x} $1; fi # done"),
+            [
+                (Keyword, "if"),
+                (String, "\"$HOME\""),
+                (Keyword, "then"),
+                (Meta, "${#x}"),
+                (Meta, "$1"),
+                (Keyword, "fi"),
+                (Comment, "# done"),
+            ]
+        );
+        assert_eq!(
+            tokens("diff", "--- a\n+++ b\n@@ -1 +1 @@\n-old\n+new\n same\n"),
+            [
+                (Meta, "--- a\n"),
+                (Meta, "+++ b\n"),
+                (Meta, "@@ -1 +1 @@\n"),
+                (Deleted, "-old\n"),
+                (Inserted, "+new\n"),
+            ]
+        );
+        assert!(Builtin.highlight("cobol", "DISPLAY 'HI'").is_none());
+    }
+}
@@ -0,0 +1,822 @@
+//! HTML renderer and supporting types.
+//!
+//! The `html` module turns [`crate::Block`] and [`crate::Inline`]
+//! structures into HTML fragments with escaped text and attributes. Link
+//! and image URLs are checked against [`Style::url_schemes`], so untrusted
+//! documents cannot smuggle in `javascript:` links.
+//!
+//! # Examples
+//! ```rust
+//! use docloom::html::{Style, doc};
+//! use docloom::prelude::*;
+//!
+//! // Optional style configuration
+//! let style = Style::default()
+//!     .language_prefix("lang-")
+//!     .max_heading(3)
+//!     .url_schemes(&["https", "mailto"]);
+//!
+//! let rendered = doc([
+//!     h1("Docloom"),
+//!     p("Render HTML from structured blocks."),
+//!     ul(["Publish reports.", "Keep table alignment."]),
+//! ])
+//! .with_style(style)
+//! .to_string();
+//! assert!(rendered.contains("<h1 id=\"docloom\">Docloom</h1>"));
+//! ```
+
+use itemize::IntoItems;
+use std::{fmt, io, sync::Arc};
+
+use super::{
+    Alignment, Block, Cell, Inline, IoWriter, Render, Renderable, Slugger, footnote,
+    highlight::{Highlighter, TokenKind},
+    plain_text, stream,
+    table::{self, Placed},
+};
+
+/// HTML document wrapper that renders blocks with a [`Style`].
+pub struct Doc {
+    content: Vec<Block>,
+    style: Style,
+}
+
+impl Doc {
+    /// Create a new document from items convertible to [`Block`].
+    pub fn new(value: impl IntoItems<Block>) -> Self {
+        Self {
+            content: value.into_items().collect(),
+            style: Style::default(),
+        }
+    }
+
+    /// Override the rendering style to use when formatting the document.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Stream the document to an [`io::Write`] target such as a file or
+    /// stdout, without building an intermediate string.
+    pub fn write_to(&self, writer: impl io::Write) -> io::Result<()> {
+        stream::write_to(writer, |out| self.render(out))
+    }
+}
+
+impl Doc {
+    fn render(&self, writer: &mut impl fmt::Write) -> fmt::Result {
+        let mut renderer = Renderer::with_style(writer, self.style.clone());
+        match footnote::collect(&self.content) {
+            Some((body, notes)) => {
+                body.render_with(&mut renderer)?;
+                writeln!(renderer.writer, "<section class=\"footnotes\">")?;
+                notes.render_with(&mut renderer)?;
+                writeln!(renderer.writer, "</section>")
+            }
+            None => self.content.render_with(&mut renderer),
+        }
+    }
+}
+
+impl fmt::Display for Doc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f)
+    }
+}
+
+/// Construct a [`Doc`] from any value that can become a sequence of blocks.
+pub fn doc(value: impl IntoItems<Block>) -> Doc {
+    Doc::new(value)
+}
+
+/// Configuration values that affect HTML output.
+///
+/// Start from [`Style::default`] and adjust it with the builder methods, so
+/// new options can be added without breaking existing code.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct Style {
+    /// Prefix added to a code block language to form its `class` attribute.
+    pub language_prefix: &'static str,
+    /// Maximum heading level emitted when rendering blocks.
+    pub max_heading: u8,
+    /// Highlighter that wraps code tokens in `<span class="hl-keyword">` and
+    /// similar elements, for styling with CSS.
+    pub highlighter: Option<Arc<dyn Highlighter + Send + Sync>>,
+    /// URL schemes allowed in link and image URLs, compared without regard
+    /// to case. Relative URLs are always allowed; links with any other
+    /// scheme render as plain text and images lose their `src`.
+    pub url_schemes: &'static [&'static str],
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            language_prefix: "language-",
+            max_heading: 6,
+            highlighter: None,
+            url_schemes: &["http", "https", "mailto"],
+        }
+    }
+}
+
+impl Style {
+    /// Set the prefix that forms a code block's `class` from its language.
+    pub fn language_prefix(mut self, language_prefix: &'static str) -> Self {
+        self.language_prefix = language_prefix;
+        self
+    }
+
+    /// Clamp heading levels to at most `max_heading`.
+    pub fn max_heading(mut self, max_heading: u8) -> Self {
+        self.max_heading = max_heading;
+        self
+    }
+
+    /// Wrap code tokens from `highlighter` in spans, or disable highlighting
+    /// with `None`.
+    pub fn highlighter(mut self, highlighter: Option<Arc<dyn Highlighter + Send + Sync>>) -> Self {
+        self.highlighter = highlighter;
+        self
+    }
+
+    /// Allow only `url_schemes`, besides relative URLs, in links and images.
+    pub fn url_schemes(mut self, url_schemes: &'static [&'static str]) -> Self {
+        self.url_schemes = url_schemes;
+        self
+    }
+
+    /// Whether `url` is relative or uses one of the allowed schemes.
+    fn allows(&self, url: &str) -> bool {
+        // Browsers ignore leading controls and spaces, and tabs or newlines
+        // anywhere, so `java\tscript:` is still a scheme
+        let url: String = url
+            .trim_start_matches(|c: char| c <= ' ')
+            .chars()
+            .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+            .collect();
+        match url.find([':', '/', '?', '#']) {
+            Some(end) if url[end..].starts_with(':') => {
+                let scheme = &url[..end];
+                self.url_schemes
+                    .iter()
+                    .any(|s| s.eq_ignore_ascii_case(scheme))
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Renderer that writes HTML to any [`fmt::Write`] target.
+///
+/// Headings get an `id`: their explicit one, or a GitHub-compatible slug of
+/// their text, so links from [`crate::toc`] resolve.
+pub struct Renderer<'a, W> {
+    writer: &'a mut W,
+    style: Style,
+    slugger: Slugger,
+}
+
+impl<'a, W> Renderer<'a, W> {
+    /// Create a renderer that writes to `writer` with the default [`Style`].
+    pub fn new(writer: &'a mut W) -> Self {
+        Self::with_style(writer, Style::default())
+    }
+
+    /// Create a renderer with a custom [`Style`].
+    pub fn with_style(writer: &'a mut W, style: Style) -> Self {
+        Self {
+            writer,
+            style,
+            slugger: Slugger::default(),
+        }
+    }
+
+    /// Render an arbitrary [`crate::Renderable`] value to the writer.
+    pub fn render<R>(&mut self, r: &R) -> Result<(), fmt::Error>
+    where
+        R: for<'b> Renderable<Renderer<'b, W>, Output = Result<(), fmt::Error>> + ?Sized,
+    {
+        r.render_with(self)
+    }
+}
+
+impl<'a, W: io::Write> Renderer<'a, IoWriter<W>> {
+    /// Create a renderer that streams to the [`io::Write`] target in `writer`.
+    pub fn from_io(writer: &'a mut IoWriter<W>) -> Self {
+        Self::new(writer)
+    }
+
+    /// Create a streaming renderer with a custom [`Style`].
+    pub fn from_io_with_style(writer: &'a mut IoWriter<W>, style: Style) -> Self {
+        Self::with_style(writer, style)
+    }
+
+    /// Render a value, returning any error from the underlying writer.
+    pub fn render_io<R>(&mut self, r: &R) -> io::Result<()>
+    where
+        R: for<'b> Renderable<Renderer<'b, IoWriter<W>>, Output = Result<(), fmt::Error>> + ?Sized,
+    {
+        let result = r.render_with(self);
+        self.writer.check(result)
+    }
+}
+
+impl Renderer<'_, String> {
+    /// Render a value to a [`String`] using the default [`Style`].
+    pub fn to_string<R>(r: &R) -> String
+    where
+        R: for<'b> Renderable<Renderer<'b, String>, Output = Result<(), fmt::Error>> + ?Sized,
+    {
+        Self::to_string_with_style(r, Style::default())
+    }
+
+    /// Render a value to a [`String`] using the default style, returning errors.
+    pub fn try_to_string<R>(r: &R) -> Result<String, fmt::Error>
+    where
+        R: for<'b> Renderable<Renderer<'b, String>, Output = Result<(), fmt::Error>> + ?Sized,
+    {
+        Self::try_to_string_with_style(r, Style::default())
+    }
+
+    /// Render a value to a [`String`] with a custom [`Style`].
+    pub fn to_string_with_style<R>(r: &R, style: Style) -> String
+    where
+        R: for<'b> Renderable<Renderer<'b, String>, Output = Result<(), fmt::Error>> + ?Sized,
+    {
+        Self::try_to_string_with_style(r, style).unwrap()
+    }
+
+    /// Render a value to a [`String`] with a custom [`Style`], returning errors.
+    pub fn try_to_string_with_style<R>(r: &R, style: Style) -> Result<String, fmt::Error>
+    where
+        R: for<'b> Renderable<Renderer<'b, String>, Output = Result<(), fmt::Error>> + ?Sized,
+    {
+        let mut buf = String::new();
+        r.render_with(&mut Renderer::with_style(&mut buf, style))?;
+        Ok(buf)
+    }
+}
+
+impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
+    type Output = Result<(), fmt::Error>;
+
+    /// Render a [`crate::Block`] into HTML.
+    fn render_block(&mut self, inner: &Block) -> Self::Output {
+        use Block::*;
+        match inner {
+            Paragraph(inner) => {
+                write!(self.writer, "<p>")?;
+                inner.render_with(self)?;
+                writeln!(self.writer, "</p>")
+            }
+            Heading { level, content, id } => {
+                let level = (*level).clamp(1, self.style.max_heading.clamp(1, 6));
+                // Slugs follow the same rules as the table of contents
+                let slug = self.slugger.slug(&plain_text(content));
+                if let Some(id) = id {
+                    self.slugger.reserve(id);
+                }
+                let id = id.as_ref().unwrap_or(&slug);
+                write!(self.writer, "<h{level} id=\"{}\">", Escape::attr(id))?;
+                content.render_with(self)?;
+                writeln!(self.writer, "</h{level}>")
+            }
+            CodeBlock {
+                language,
+                content,
+                meta,
+            } => {
+                if let Some(title) = &meta.title {
+                    write!(
+                        self.writer,
+                        "<figure><figcaption>{}</figcaption>",
+                        Escape::text(title)
+                    )?;
+                }
+                // Attributes understood by Prism's line plugins
+                write!(self.writer, "<pre")?;
+                if let Some(start) = meta.start_line {
+                    write!(self.writer, " data-start=\"{start}\"")?;
+                }
+                if !meta.highlighted.is_empty() {
+                    write!(self.writer, " data-line=\"{}\"", meta.highlighted_ranges())?;
+                }
+                write!(self.writer, "><code")?;
+                if let Some(lang) = language {
+                    write!(
+                        self.writer,
+                        " class=\"{}{}\"",
+                        Escape::attr(self.style.language_prefix),
+                        Escape::attr(lang)
+                    )?;
+                }
+                write!(self.writer, ">")?;
+                let tokens = language
+                    .as_deref()
+                    .zip(self.style.highlighter.as_deref())
+                    .and_then(|(language, highlighter)| highlighter.highlight(language, content));
+                match tokens {
+                    Some(tokens) => {
+                        for token in tokens {
+                            match token.kind {
+                                TokenKind::Plain => {
+                                    write!(self.writer, "{}", Escape::text(token.text))?
+                                }
+                                kind => write!(
+                                    self.writer,
+                                    "<span class=\"hl-{}\">{}</span>",
+                                    kind.name(),
+                                    Escape::text(token.text)
+                                )?,
+                            }
+                        }
+                    }
+                    None => write!(self.writer, "{}", Escape::text(content))?,
+                }
+                write!(self.writer, "</code></pre>")?;
+                if meta.title.is_some() {
+                    write!(self.writer, "</figure>")?;
+                }
+                writeln!(self.writer)
+            }
+            List { ordered, items } => {
+                let tag = if *ordered { "ol" } else { "ul" };
+                writeln!(self.writer, "<{tag}>")?;
+                for item in items {
+                    write!(self.writer, "<li>")?;
+                    self.render_item(item)?;
+                    writeln!(self.writer, "</li>")?;
+                }
+                writeln!(self.writer, "</{tag}>")
+            }
+            TaskList { items } => {
+                writeln!(self.writer, "<ul class=\"task-list\">")?;
+                for (checked, item) in items {
+                    let checked = if *checked { " checked" } else { "" };
+                    write!(
+                        self.writer,
+                        "<li class=\"task-list-item\"><input type=\"checkbox\" disabled{checked}> "
+                    )?;
+                    self.render_item(item)?;
+                    writeln!(self.writer, "</li>")?;
+                }
+                writeln!(self.writer, "</ul>")
+            }
+            Table {
+                headers,
+                rows,
+                alignments,
+                footer,
+                caption,
+            } => {
+                writeln!(self.writer, "<table>")?;
+                if let Some(caption) = caption {
+                    writeln!(self.writer, "<caption>{}</caption>", Escape::text(caption))?;
+                }
+                let columns = table::column_count(headers);
+                writeln!(self.writer, "<thead>")?;
+                self.render_rows("th", std::slice::from_ref(headers), columns, alignments)?;
+                writeln!(self.writer, "</thead>")?;
+
+                if !rows.is_empty() {
+                    writeln!(self.writer, "<tbody>")?;
+                    self.render_rows("td", rows, columns, alignments)?;
+                    writeln!(self.writer, "</tbody>")?;
+                }
+                if !footer.is_empty() {
+                    writeln!(self.writer, "<tfoot>")?;
+                    self.render_rows("td", footer, columns, alignments)?;
+                    writeln!(self.writer, "</tfoot>")?;
+                }
+                writeln!(self.writer, "</table>")
+            }
+            Blockquote(inner) => {
+                writeln!(self.writer, "<blockquote>")?;
+                inner.render_with(self)?;
+                writeln!(self.writer, "</blockquote>")
+            }
+            Image { alt, url } => {
+                write!(self.writer, "<p>")?;
+                self.write_image(alt, url)?;
+                writeln!(self.writer, "</p>")
+            }
+            HorizontalRule => writeln!(self.writer, "<hr>"),
+            BlockList(inner) => {
+                for block in inner.iter() {
+                    block.render_with(self)?;
+                }
+                Ok(())
+            }
+            Callout {
+                kind,
+                title,
+                content,
+            } => {
+                // The markup GitHub uses for alerts, so its styles apply
+                writeln!(
+                    self.writer,
+                    "<div class=\"markdown-alert markdown-alert-{}\">",
+                    kind.name()
+                )?;
+                writeln!(
+                    self.writer,
+                    "<p class=\"markdown-alert-title\">{}</p>",
+                    Escape::text(title.as_deref().unwrap_or(kind.label()))
+                )?;
+                content.render_with(self)?;
+                writeln!(self.writer, "</div>")
+            }
+            FootnoteDefinition { label, content } => {
+                let label = Escape::attr(label);
+                writeln!(
+                    self.writer,
+                    "<div class=\"footnote\" id=\"fn-{label}\"><sup>{label}</sup>"
+                )?;
+                content.render_with(self)?;
+                writeln!(self.writer, "</div>")
+            }
+            DefinitionList(items) => {
+                writeln!(self.writer, "<dl>")?;
+                for (term, definition) in items {
+                    write!(self.writer, "<dt>")?;
+                    term.render_with(self)?;
+                    writeln!(self.writer, "</dt>")?;
+                    write!(self.writer, "<dd>")?;
+                    match definition.as_slice() {
+                        [Block::Paragraph(content)] => content.render_with(self)?,
+                        blocks => {
+                            writeln!(self.writer)?;
+                            blocks.render_with(self)?;
+                        }
+                    }
+                    writeln!(self.writer, "</dd>")?;
+                }
+                writeln!(self.writer, "</dl>")
+            }
+            Details {
+                summary,
+                content,
+                open,
+            } => {
+                let open = if *open { " open" } else { "" };
+                write!(self.writer, "<details{open}>\n<summary>")?;
+                summary.render_with(self)?;
+                writeln!(self.writer, "</summary>")?;
+                content.render_with(self)?;
+                writeln!(self.writer, "</details>")
+            }
+        }
+    }
+
+    /// Render an [`crate::Inline`] into HTML.
+    fn render_inline(&mut self, inner: &Inline) -> fmt::Result {
+        use Inline::*;
+        match inner {
+            Text(text) => write!(self.writer, "{}", Escape::text(text)),
+            Bold(inner) => {
+                write!(self.writer, "<strong>")?;
+                inner.render_with(self)?;
+                write!(self.writer, "</strong>")
+            }
+            Italic(inner) => {
+                write!(self.writer, "<em>")?;
+                inner.render_with(self)?;
+                write!(self.writer, "</em>")
+            }
+            Strikethrough(inner) => {
+                write!(self.writer, "<del>")?;
+                inner.render_with(self)?;
+                write!(self.writer, "</del>")
+            }
+            Code(text) => write!(self.writer, "<code>{}</code>", Escape::text(text)),
+            Link { text, url } if !self.style.allows(url) => text.render_with(self),
+            Link { text, url } => {
+                write!(self.writer, "<a href=\"{}\">", Escape::attr(url))?;
+                text.render_with(self)?;
+                write!(self.writer, "</a>")
+            }
+            Image { alt, url } => self.write_image(alt, url),
+            LineBreak => writeln!(self.writer, "<br>"),
+            FootnoteRef(label) => write!(
+                self.writer,
+                "<sup class=\"footnote-ref\"><a href=\"#fn-{0}\">{0}</a></sup>",
+                Escape::attr(label)
+            ),
+        }
+    }
+}
+
+impl<'a, W: fmt::Write> Renderer<'a, W> {
+    /// Render a list item, keeping single paragraphs tight inside the `<li>`.
+    fn render_item(&mut self, item: &Block) -> fmt::Result {
+        match item {
+            Block::Paragraph(content) => content.render_with(self),
+            block => {
+                writeln!(self.writer)?;
+                block.render_with(self)
+            }
+        }
+    }
+
+    /// Write an `<img>`, leaving out `src` when its scheme is not allowed.
+    fn write_image(&mut self, alt: &str, url: &str) -> fmt::Result {
+        write!(self.writer, "<img")?;
+        if self.style.allows(url) {
+            write!(self.writer, " src=\"{}\"", Escape::attr(url))?;
+        }
+        write!(self.writer, " alt=\"{}\">", Escape::attr(alt))
+    }
+
+    /// Render the rows of a table section, with spans as placed on the grid.
+    fn render_rows(
+        &mut self,
+        tag: &str,
+        rows: &[Vec<Cell>],
+        columns: usize,
+        alignments: &[Alignment],
+    ) -> fmt::Result {
+        for (r, row) in table::layout(rows, columns).iter().enumerate() {
+            writeln!(self.writer, "<tr>")?;
+            for (c, slot) in row.iter().enumerate() {
+                match slot {
+                    Some(placed) if placed.starts_at(r, c) => {
+                        self.render_cell(tag, Some(placed), alignments.get(c))?
+                    }
+                    Some(_) => {}
+                    // Empty cell if row doesn't have enough columns
+                    None => self.render_cell(tag, None, alignments.get(c))?,
+                }
+            }
+            writeln!(self.writer, "</tr>")?;
+        }
+        Ok(())
+    }
+
+    fn render_cell(
+        &mut self,
+        tag: &str,
+        cell: Option<&Placed>,
+        align: Option<&Alignment>,
+    ) -> fmt::Result {
+        write!(self.writer, "<{tag}")?;
+        if let Some(placed) = cell.filter(|placed| placed.columns > 1) {
+            write!(self.writer, " colspan=\"{}\"", placed.columns)?;
+        }
+        if let Some(placed) = cell.filter(|placed| placed.rows > 1) {
+            write!(self.writer, " rowspan=\"{}\"", placed.rows)?;
+        }
+        match align {
+            Some(Alignment::Left) => write!(self.writer, " style=\"text-align: left\">")?,
+            Some(Alignment::Center) => write!(self.writer, " style=\"text-align: center\">")?,
+            Some(Alignment::Right) => write!(self.writer, " style=\"text-align: right\">")?,
+            None => write!(self.writer, ">")?,
+        }
+        if let Some(placed) = cell {
+            placed.cell.content.render_with(self)?;
+        }
+        writeln!(self.writer, "</{tag}>")
+    }
+}
+
+/// Display adapter that escapes HTML special characters.
+struct Escape<'a> {
+    value: &'a str,
+    quotes: bool,
+}
+
+impl<'a> Escape<'a> {
+    /// Escape text content (`&`, `<`, `>`).
+    fn text(value: &'a str) -> Self {
+        Self {
+            value,
+            quotes: false,
+        }
+    }
+
+    /// Escape attribute values, including quote characters.
+    fn attr(value: &'a str) -> Self {
+        Self {
+            value,
+            quotes: true,
+        }
+    }
+}
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+        for c in self.value.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' if self.quotes => f.write_str("&quot;")?,
+                '\'' if self.quotes => f.write_str("&#39;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::*;
+
+    #[test]
+    fn test_html_heading_ids() {
+        let blocks = [
+            h1("Guide"),
+            h2(("Install ", code("cargo"))),
+            h2("Usage").with_id("how-to"),
+            h2("Usage"),
+            h2("Install cargo"),
+        ];
+        let mut page = vec![crate::toc(&blocks)];
+        page.extend(blocks);
+        let html = Renderer::to_string(&page[..]);
+        assert!(html.ends_with(
+            "<h1 id=\"guide\">Guide</h1>\n\
+             <h2 id=\"install-cargo\">Install <code>cargo</code></h2>\n\
+             <h2 id=\"how-to\">Usage</h2>\n\
+             <h2 id=\"usage-1\">Usage</h2>\n\
+             <h2 id=\"install-cargo-1\">Install cargo</h2>\n"
+        ));
+        // Every contents entry points at a heading
+        for anchor in [
+            "guide",
+            "install-cargo",
+            "how-to",
+            "usage-1",
+            "install-cargo-1",
+        ] {
+            assert!(
+                html.contains(&format!("<a href=\"#{anchor}\">")),
+                "{anchor}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_html_url_schemes() {
+        let links = p((
+            link("a", "https://docs.rs"),
+            link("b", "../guide.html#setup"),
+            link("c", "MAILTO:me@example.com"),
+            link("d", "javascript:alert(1)"),
+            link("e", " Java\tScript:alert(1)"),
+            link("f", "data:text/html,<script>"),
+        ));
+        assert_eq!(
+            Renderer::to_string(&links).trim(),
+            "<p><a href=\"https://docs.rs\">a</a><a href=\"../guide.html#setup\">b</a>\
+             <a href=\"MAILTO:me@example.com\">c</a>def</p>"
+        );
+
+        let image = Block::Image {
+            alt: "x".into(),
+            url: "javascript:alert(1)".into(),
+        };
+        assert_eq!(Renderer::to_string(&image).trim(), "<p><img alt=\"x\"></p>");
+
+        // Extra schemes can be allowed explicitly
+        let style = Style::default().url_schemes(&["data"]);
+        let html = Renderer::to_string_with_style(&links, style);
+        assert!(html.contains("<a href=\"data:text/html,&lt;script&gt;\">f</a>"));
+        assert!(!html.contains("https://docs.rs"));
+    }
+
+    #[test]
+    fn test_html_escaping() {
+        let html = Renderer::to_string(&p(("a < b & c", link("x", "/q?a=1&b=\"2\""))));
+        assert_eq!(
+            html.trim(),
+            "<p>a &lt; b &amp; c<a href=\"/q?a=1&amp;b=&quot;2&quot;\">x</a></p>"
+        );
+
+        let html = Renderer::to_string(&code_block("rust", "if a < b && c > d {}"));
+        assert_eq!(
+            html.trim(),
+            "<pre><code class=\"language-rust\">if a &lt; b &amp;&amp; c &gt; d {}</code></pre>"
+        );
+
+        let style = Style {
+            highlighter: Some(Arc::new(crate::highlight::Builtin)),
+            ..Style::default()
+        };
+        let html = Renderer::to_string_with_style(&code_block("rust", "let a = \"<b>\";"), style);
+        assert_eq!(
+            html.trim(),
+            "<pre><code class=\"language-rust\"><span class=\"hl-keyword\">let</span> a = \
+             <span class=\"hl-string\">\"&lt;b&gt;\"</span>;</code></pre>"
+        );
+
+        let snippet = code_block((), "x")
+            .with_title("<main>.rs")
+            .with_start_line(9)
+            .with_highlighted([9, 10]);
+        assert_eq!(
+            Renderer::to_string(&snippet).trim(),
+            "<figure><figcaption>&lt;main&gt;.rs</figcaption>\
+             <pre data-start=\"9\" data-line=\"9-10\"><code>x</code></pre></figure>"
+        );
+
+        let html = Renderer::to_string(&h2("Usage").with_id("use \"it\""));
+        assert_eq!(html.trim(), "<h2 id=\"use &quot;it&quot;\">Usage</h2>");
+    }
+
+    #[test]
+    fn test_html_footnotes() {
+        let (cite, note) = footnote("Source & notes");
+        let html = doc([note, p(("Claim", cite))]).to_string();
+        assert_eq!(
+            html,
+            "<p>Claim<sup class=\"footnote-ref\"><a href=\"#fn-1\">1</a></sup></p>\n\
+             <section class=\"footnotes\">\n\
+             <div class=\"footnote\" id=\"fn-1\"><sup>1</sup>\n\
+             <p>Source &amp; notes</p>\n</div>\n</section>\n"
+        );
+    }
+
+    #[test]
+    fn test_html_table_alignment() {
+        let table = table(
+            (
+                Align::left("Name"),
+                Align::center("Age"),
+                Align::right("Score"),
+            ),
+            (("Alice", "30", "95"), ("Bob", "25")),
+        );
+        let html = Renderer::to_string(&table);
+        assert!(html.contains("<th style=\"text-align: left\">Name</th>"));
+        assert!(html.contains("<th style=\"text-align: center\">Age</th>"));
+        assert!(html.contains("<td style=\"text-align: right\">95</td>"));
+        // Missing cells are padded out to the header count
+        assert!(html.contains("<td style=\"text-align: right\"></td>"));
+    }
+
+    #[test]
+    fn test_html_table_spans() {
+        let table = table(
+            ("Region", cell("Sales").with_colspan(2)),
+            [
+                vec![cell("North").with_rowspan(2), cell("Q1"), cell("10")],
+                vec![cell("Q2"), cell("12")],
+            ],
+        )
+        .with_footer([(cell("Total").with_colspan(2), "22")])
+        .with_caption("Sales");
+        let html = Renderer::to_string(&table);
+        assert!(html.starts_with("<table>\n<caption>Sales</caption>\n<thead>"));
+        assert!(html.contains("<th colspan=\"2\" style=\"text-align: left\">Sales</th>"));
+        assert!(html.contains("<td rowspan=\"2\" style=\"text-align: left\">North</td>"));
+        // Positions covered by a span get no cell of their own
+        assert!(html.contains("<tr>\n<td style=\"text-align: left\">Q2</td>"));
+        assert!(html.contains(
+            "<tfoot>\n<tr>\n<td colspan=\"2\" style=\"text-align: left\">Total</td>\n\
+             <td style=\"text-align: left\">22</td>\n</tr>\n</tfoot>"
+        ));
+    }
+
+    #[test]
+    fn test_html_lists_and_quotes() {
+        let html = Renderer::to_string(&task_list([(true, "Done"), (false, "Todo")]));
+        assert!(html.contains("<input type=\"checkbox\" disabled checked> Done</li>"));
+        assert!(html.contains("<input type=\"checkbox\" disabled> Todo</li>"));
+
+        let html = Renderer::to_string(&ol(["One", "Two"]));
+        assert_eq!(html.trim(), "<ol>\n<li>One</li>\n<li>Two</li>\n</ol>");
+
+        let html = Renderer::to_string(&important(p("Read <this>.")).with_title("Heads up"));
+        assert_eq!(
+            html,
+            "<div class=\"markdown-alert markdown-alert-important\">\n\
+             <p class=\"markdown-alert-title\">Heads up</p>\n<p>Read &lt;this&gt;.</p>\n</div>\n"
+        );
+
+        let html = Renderer::to_string(&dl((
+            ("Term", "Short."),
+            ("Long", block((p("One."), hr()))),
+        )));
+        assert_eq!(
+            html,
+            "<dl>\n<dt>Term</dt>\n<dd>Short.</dd>\n<dt>Long</dt>\n<dd>\n<p>One.</p>\n<hr>\n</dd>\n</dl>\n"
+        );
+
+        let html = Renderer::to_string(&details(bold("Log"), p("Done.")).with_open(true));
+        assert_eq!(
+            html,
+            "<details open>\n<summary><strong>Log</strong></summary>\n<p>Done.</p>\n</details>\n"
+        );
+
+        let html = Renderer::to_string(&quote((p("Quoted."), hr())));
+        assert_eq!(
+            html.trim(),
+            "<blockquote>\n<p>Quoted.</p>\n<hr>\n</blockquote>"
+        );
+    }
+}
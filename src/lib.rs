@@ -1,8 +1,18 @@
 //! Flexible tools for assembling and rendering structured documentation trees.
 //!
 //! Build documents from [`Block`] and [`Inline`] nodes, then render them using
-//! Markdown-friendly [`md`] output, ANSI-aware [`term`] output, or a custom
-//! [`Render`] implementation.
+//! Markdown-friendly [`md`] output, ANSI-aware [`term`] output, escaped [`html`]
+//! output, or a custom [`Render`] implementation. Walk or rewrite trees with
+//! [`Visitor`], [`VisitorMut`] and [`Fold`]. Code blocks are syntax
+//! highlighted through the [`highlight::Highlighter`] trait.
+//!
+//! With the `serde` feature enabled, [`Block`], [`Inline`], [`Cell`],
+//! [`Alignment`] and [`CalloutKind`] implement `Serialize` and `Deserialize`
+//! using a tagged schema: every node is an object with a snake_case `type`
+//! field, single-value variants keep their value under `content`, task list
+//! items become `{ "checked": bool, "content": Block }`, definition list items
+//! become `{ "term": [Inline], "content": [Block] }` and table cells become
+//! `{ "content": [Inline] }` with optional `colspan` and `rowspan`.
 //!
 //! # Examples
 //! ```rust
@@ -44,49 +54,130 @@
 //! ```
 
 mod build;
+mod footnote;
+mod number;
+#[cfg(feature = "serde")]
+mod schema;
+mod slug;
+mod stream;
+mod table;
+mod toc;
+mod width;
 
+pub mod highlight;
+pub mod html;
 pub mod md;
 pub mod term;
 
 /// Convenience re-exports of builder helpers and extension traits.
 pub mod prelude {
     pub use crate::build::{
-        Align, BlockExt, InlineExt, block, bold, code, code_block, h1, h2, h3, h4, h5, h6, hr,
-        italic, link, ol, p, quote, strikethrough, table, task_list, text, ul,
+        Align, BlockExt, InlineExt, block, bold, callout, cell, code, code_block, danger, details,
+        dl, footnote, h1, h2, h3, h4, h5, h6, hr, important, italic, link, note, ol, p, quote,
+        strikethrough, table, task_list, text, tip, ul, warning, xref,
     };
+    pub use crate::toc::toc;
 }
 
+pub use number::{LevelFormat, NumberStyle, Numbering};
+pub use slug::Slugger;
+pub use stream::IoWriter;
+pub use toc::{Toc, TocPosition, toc};
+
 /// Document-level nodes describing paragraphs, lists, and other structural items.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, itemize::IntoItems, itemize::IntoRows)]
 #[items_from(types(Block, Inline), tuples(12), collections(vec, slice, array))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum Block {
     /// A paragraph of inline elements.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
     Paragraph(Vec<Inline>),
-    /// A heading with a specific level and inline content.
-    Heading { level: u8, content: Vec<Inline> },
-    /// A fenced code block with an optional language tag.
+    /// A heading with a specific level, inline content and an optional
+    /// explicit anchor id.
+    Heading {
+        /// Level from 1 to 6; builders, deserialization and renderers clamp
+        /// levels outside that range.
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "schema::heading_level"))]
+        level: u8,
+        content: Vec<Inline>,
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        id: Option<String>,
+    },
+    /// A fenced code block with an optional language tag and presentation
+    /// details such as a title and line numbers.
     CodeBlock {
         language: Option<String>,
         content: String,
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        meta: CodeMeta,
     },
     /// A nested collection of quoted blocks.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
     Blockquote(Vec<Block>),
     /// An ordered or unordered list of blocks.
     List { ordered: bool, items: Vec<Block> },
     /// A list of checkbox items paired with their content.
-    TaskList { items: Vec<(bool, Block)> },
-    /// A table with headers, rows, and column alignments.
+    TaskList {
+        #[cfg_attr(feature = "serde", serde(with = "schema::tasks"))]
+        items: Vec<(bool, Block)>,
+    },
+    /// A table with headers, rows, footer rows, an optional caption and an
+    /// alignment for each column.
+    ///
+    /// Cells covered by a [`Cell`] spanning several rows are left out of the
+    /// rows below it, as in HTML.
     Table {
-        headers: Vec<Inline>,
-        rows: Vec<Vec<Inline>>,
+        headers: Vec<Cell>,
+        rows: Vec<Vec<Cell>>,
         alignments: Vec<Alignment>,
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Vec::is_empty")
+        )]
+        footer: Vec<Vec<Cell>>,
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        caption: Option<String>,
     },
     /// A standalone image block.
     Image { alt: String, url: String },
     /// A thematic break separating sections.
     HorizontalRule,
     /// A container that renders nested blocks in sequence.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
     BlockList(Vec<Block>),
+    /// The content of the footnote that [`Inline::FootnoteRef`]s with the
+    /// same label point to.
+    FootnoteDefinition { label: String, content: Vec<Block> },
+    /// A highlighted note or warning, titled with its kind unless a title
+    /// is given.
+    Callout {
+        kind: CalloutKind,
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        title: Option<String>,
+        content: Vec<Block>,
+    },
+    /// Terms paired with the blocks that define them, such as command-line
+    /// flags or configuration keys with their descriptions.
+    #[cfg_attr(feature = "serde", serde(with = "schema::definitions"))]
+    DefinitionList(Vec<(Vec<Inline>, Vec<Block>)>),
+    /// A collapsible section showing its summary, with the content hidden
+    /// until expanded unless `open` is set.
+    Details {
+        summary: Vec<Inline>,
+        content: Vec<Block>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        open: bool,
+    },
 }
 
 impl<T> From<T> for Block
@@ -100,17 +191,24 @@ where
 
 /// Inline elements that compose textual content.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, itemize::IntoItems, itemize::IntoRows)]
-#[items_from(types(&'a str, String, &'a String, usize, bool, f32, f64), tuples(12), collections(vec, slice, array))]
+#[items_from(types(Inline, &'a str, String, &'a String, usize, bool, f32, f64), tuples(12), collections(vec, slice, array))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum Inline {
     /// Plain text.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
     Text(String),
     /// Bold inline content.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
     Bold(Vec<Inline>),
     /// Italic inline content.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
     Italic(Vec<Inline>),
     /// Struck-through inline content.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
     Strikethrough(Vec<Inline>),
     /// Inline code snippet.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
     Code(String),
     /// A hyperlink with inline text and destination.
     Link { text: Vec<Inline>, url: String },
@@ -118,6 +216,9 @@ pub enum Inline {
     Image { alt: String, url: String },
     /// A hard line break.
     LineBreak,
+    /// A reference to the [`Block::FootnoteDefinition`] with this label.
+    #[cfg_attr(feature = "serde", serde(with = "schema::content"))]
+    FootnoteRef(String),
 }
 
 impl<T> From<T> for Inline
@@ -129,8 +230,111 @@ where
     }
 }
 
+/// Optional presentation details of a [`Block::CodeBlock`].
+///
+/// # Examples
+/// ```rust
+/// use docloom::prelude::*;
+///
+/// let snippet = code_block("rust", "let x = 1;\nlet y = x / 0;")
+///     .with_title("main.rs")
+///     .with_start_line(41)
+///     .with_highlighted([42]);
+/// let markdown = docloom::md::doc(snippet).to_string();
+/// assert!(markdown.starts_with("```rust title=\"main.rs\" showLineNumbers=41 {42}\n"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CodeMeta {
+    /// Caption shown with the code, such as a file name.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub title: Option<String>,
+    /// Number of the first line; line numbers are only shown when set.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub start_line: Option<usize>,
+    /// Lines to emphasize, numbered as displayed, so counting from
+    /// `start_line` or 1.
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "std::collections::BTreeSet::is_empty")
+    )]
+    pub highlighted: std::collections::BTreeSet<usize>,
+}
+
+impl CodeMeta {
+    /// Whether no details are set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Highlighted lines as `3-5,8`, collapsing consecutive lines into
+    /// ranges.
+    pub(crate) fn highlighted_ranges(&self) -> String {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for &line in &self.highlighted {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == line => *end = line,
+                _ => ranges.push((line, line)),
+            }
+        }
+        let ranges: Vec<String> = ranges
+            .into_iter()
+            .map(|(start, end)| {
+                if start == end {
+                    start.to_string()
+                } else {
+                    format!("{start}-{end}")
+                }
+            })
+            .collect();
+        ranges.join(",")
+    }
+}
+
+/// A table cell holding inline content, which may span several columns or
+/// rows.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, itemize::IntoItems, itemize::IntoRows)]
+#[items_from(types(Cell, Inline, &'a str, String, &'a String, usize, bool, f32, f64), tuples(12), collections(vec, slice, array))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cell {
+    /// Inline content of the cell.
+    pub content: Vec<Inline>,
+    /// Number of columns the cell covers, where 0 counts as 1.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "schema::one",
+            deserialize_with = "schema::span",
+            skip_serializing_if = "schema::is_one"
+        )
+    )]
+    pub colspan: usize,
+    /// Number of rows the cell covers, where 0 counts as 1.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "schema::one",
+            deserialize_with = "schema::span",
+            skip_serializing_if = "schema::is_one"
+        )
+    )]
+    pub rowspan: usize,
+}
+
+impl<T> From<T> for Cell
+where
+    T: Into<Inline>,
+{
+    fn from(value: T) -> Self {
+        build::cell(value.into())
+    }
+}
+
 /// Column alignment options used when rendering tables.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Alignment {
     /// Align content with the leading edge.
     Left,
@@ -140,6 +344,56 @@ pub enum Alignment {
     Right,
 }
 
+/// The kinds of [`Block::Callout`], matching GitHub's alerts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CalloutKind {
+    /// Information worth noticing while skimming.
+    Note,
+    /// Advice for doing things better or more easily.
+    Tip,
+    /// Information needed to succeed.
+    Important,
+    /// Urgent information that needs immediate attention.
+    Warning,
+    /// Risks or negative outcomes of an action.
+    Danger,
+}
+
+impl CalloutKind {
+    /// Every kind, in order of severity.
+    pub const ALL: [CalloutKind; 5] = [
+        CalloutKind::Note,
+        CalloutKind::Tip,
+        CalloutKind::Important,
+        CalloutKind::Warning,
+        CalloutKind::Danger,
+    ];
+
+    /// The lowercase name of the kind, such as `warning`.
+    pub fn name(self) -> &'static str {
+        match self {
+            CalloutKind::Note => "note",
+            CalloutKind::Tip => "tip",
+            CalloutKind::Important => "important",
+            CalloutKind::Warning => "warning",
+            CalloutKind::Danger => "danger",
+        }
+    }
+
+    /// The default title of the kind, such as `Warning`.
+    pub fn label(self) -> &'static str {
+        match self {
+            CalloutKind::Note => "Note",
+            CalloutKind::Tip => "Tip",
+            CalloutKind::Important => "Important",
+            CalloutKind::Warning => "Warning",
+            CalloutKind::Danger => "Danger",
+        }
+    }
+}
+
 /// Trait implemented by renderers that consume [`Block`] and [`Inline`] trees.
 pub trait Render {
     type Output;
@@ -183,3 +437,467 @@ where
         Ok(())
     }
 }
+
+/// Read-only traversal over [`Block`] and [`Inline`] trees.
+///
+/// Override the methods for the nodes you care about and call [`walk_block`]
+/// or [`walk_inline`] to continue into their children.
+///
+/// ```rust
+/// use docloom::prelude::*;
+/// use docloom::{Block, Visitor, walk_block};
+///
+/// struct CodeBlocks(Vec<String>);
+///
+/// impl Visitor for CodeBlocks {
+///     fn visit_block(&mut self, block: &Block) {
+///         if let Block::CodeBlock { content, .. } = block {
+///             self.0.push(content.clone());
+///         }
+///         walk_block(self, block);
+///     }
+/// }
+///
+/// let doc = quote((p("Run:"), code_block("sh", "cargo test")));
+/// let mut blocks = CodeBlocks(Vec::new());
+/// blocks.visit_block(&doc);
+/// assert_eq!(blocks.0, ["cargo test"]);
+/// ```
+pub trait Visitor {
+    /// Visit a block, by default walking its children.
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block);
+    }
+    /// Visit an inline node, by default walking its children.
+    fn visit_inline(&mut self, inline: &Inline) {
+        walk_inline(self, inline);
+    }
+}
+
+/// Visit the direct children of `block`, including table cells.
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &Block) {
+    match block {
+        Block::Paragraph(content) | Block::Heading { content, .. } => {
+            content.iter().for_each(|i| visitor.visit_inline(i));
+        }
+        Block::Blockquote(blocks)
+        | Block::BlockList(blocks)
+        | Block::List { items: blocks, .. }
+        | Block::FootnoteDefinition {
+            content: blocks, ..
+        }
+        | Block::Callout {
+            content: blocks, ..
+        } => {
+            blocks.iter().for_each(|b| visitor.visit_block(b));
+        }
+        Block::TaskList { items } => items.iter().for_each(|(_, b)| visitor.visit_block(b)),
+        Block::DefinitionList(items) => {
+            for (term, definition) in items {
+                term.iter().for_each(|i| visitor.visit_inline(i));
+                definition.iter().for_each(|b| visitor.visit_block(b));
+            }
+        }
+        Block::Details {
+            summary, content, ..
+        } => {
+            summary.iter().for_each(|i| visitor.visit_inline(i));
+            content.iter().for_each(|b| visitor.visit_block(b));
+        }
+        Block::Table {
+            headers,
+            rows,
+            footer,
+            ..
+        } => {
+            let cells = headers.iter().chain(rows.iter().chain(footer).flatten());
+            for inline in cells.flat_map(|cell| &cell.content) {
+                visitor.visit_inline(inline);
+            }
+        }
+        Block::CodeBlock { .. } | Block::Image { .. } | Block::HorizontalRule => {}
+    }
+}
+
+/// Visit the direct children of `inline`.
+pub fn walk_inline<V: Visitor + ?Sized>(visitor: &mut V, inline: &Inline) {
+    match inline {
+        Inline::Bold(content)
+        | Inline::Italic(content)
+        | Inline::Strikethrough(content)
+        | Inline::Link { text: content, .. } => {
+            content.iter().for_each(|i| visitor.visit_inline(i));
+        }
+        Inline::Text(_)
+        | Inline::Code(_)
+        | Inline::Image { .. }
+        | Inline::LineBreak
+        | Inline::FootnoteRef(_) => {}
+    }
+}
+
+/// The text content of inline nodes without formatting, as used for heading
+/// slugs, outline entries and image alt text. Images contribute their alt
+/// text, line breaks a space and footnote references nothing.
+pub(crate) fn plain_text(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) | Inline::Code(text) => text.clone(),
+            Inline::Bold(inner) | Inline::Italic(inner) | Inline::Strikethrough(inner) => {
+                plain_text(inner)
+            }
+            Inline::Link { text, .. } => plain_text(text),
+            Inline::Image { alt, .. } => alt.clone(),
+            Inline::LineBreak => " ".to_string(),
+            Inline::FootnoteRef(_) => String::new(),
+        })
+        .collect()
+}
+
+/// In-place traversal over [`Block`] and [`Inline`] trees.
+///
+/// ```rust
+/// use docloom::prelude::*;
+/// use docloom::{Inline, VisitorMut, walk_inline_mut};
+///
+/// struct LowercaseUrls;
+///
+/// impl VisitorMut for LowercaseUrls {
+///     fn visit_inline_mut(&mut self, inline: &mut Inline) {
+///         if let Inline::Link { url, .. } = inline {
+///             *url = url.to_lowercase();
+///         }
+///         walk_inline_mut(self, inline);
+///     }
+/// }
+///
+/// let mut doc = p(vec![link("Docs", "HTTPS://Docs.RS")]);
+/// LowercaseUrls.visit_block_mut(&mut doc);
+/// assert_eq!(doc, p(vec![link("Docs", "https://docs.rs")]));
+/// ```
+pub trait VisitorMut {
+    /// Visit a block, by default walking its children.
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block_mut(self, block);
+    }
+    /// Visit an inline node, by default walking its children.
+    fn visit_inline_mut(&mut self, inline: &mut Inline) {
+        walk_inline_mut(self, inline);
+    }
+}
+
+/// Mutably visit the direct children of `block`, including table cells.
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block) {
+    match block {
+        Block::Paragraph(content) | Block::Heading { content, .. } => {
+            content.iter_mut().for_each(|i| visitor.visit_inline_mut(i));
+        }
+        Block::Blockquote(blocks)
+        | Block::BlockList(blocks)
+        | Block::List { items: blocks, .. }
+        | Block::FootnoteDefinition {
+            content: blocks, ..
+        }
+        | Block::Callout {
+            content: blocks, ..
+        } => {
+            blocks.iter_mut().for_each(|b| visitor.visit_block_mut(b));
+        }
+        Block::TaskList { items } => {
+            items
+                .iter_mut()
+                .for_each(|(_, b)| visitor.visit_block_mut(b));
+        }
+        Block::DefinitionList(items) => {
+            for (term, definition) in items {
+                term.iter_mut().for_each(|i| visitor.visit_inline_mut(i));
+                definition
+                    .iter_mut()
+                    .for_each(|b| visitor.visit_block_mut(b));
+            }
+        }
+        Block::Details {
+            summary, content, ..
+        } => {
+            summary.iter_mut().for_each(|i| visitor.visit_inline_mut(i));
+            content.iter_mut().for_each(|b| visitor.visit_block_mut(b));
+        }
+        Block::Table {
+            headers,
+            rows,
+            footer,
+            ..
+        } => {
+            let cells = headers
+                .iter_mut()
+                .chain(rows.iter_mut().chain(footer).flatten());
+            for inline in cells.flat_map(|cell| &mut cell.content) {
+                visitor.visit_inline_mut(inline);
+            }
+        }
+        Block::CodeBlock { .. } | Block::Image { .. } | Block::HorizontalRule => {}
+    }
+}
+
+/// Mutably visit the direct children of `inline`.
+pub fn walk_inline_mut<V: VisitorMut + ?Sized>(visitor: &mut V, inline: &mut Inline) {
+    match inline {
+        Inline::Bold(content)
+        | Inline::Italic(content)
+        | Inline::Strikethrough(content)
+        | Inline::Link { text: content, .. } => {
+            content.iter_mut().for_each(|i| visitor.visit_inline_mut(i));
+        }
+        Inline::Text(_)
+        | Inline::Code(_)
+        | Inline::Image { .. }
+        | Inline::LineBreak
+        | Inline::FootnoteRef(_) => {}
+    }
+}
+
+/// Owning transform that can replace or remove nodes.
+///
+/// Returning `None` removes a node from its parent. Table cells keep their
+/// place, so removing all of a cell's content leaves it empty.
+///
+/// ```rust
+/// use docloom::prelude::*;
+/// use docloom::{Block, Fold, Inline, fold_inline_children};
+///
+/// struct StripImages;
+///
+/// impl Fold for StripImages {
+///     fn fold_inline(&mut self, inline: Inline) -> Option<Inline> {
+///         match inline {
+///             Inline::Image { .. } => None,
+///             inline => fold_inline_children(self, inline),
+///         }
+///     }
+/// }
+///
+/// let icon = Inline::Image { alt: "icon".into(), url: "icon.png".into() };
+/// let doc = StripImages.fold_block(p(("Logo: ", icon)));
+/// assert_eq!(doc, Some(p("Logo: ")));
+/// ```
+pub trait Fold {
+    /// Transform a block, by default folding its children.
+    fn fold_block(&mut self, block: Block) -> Option<Block> {
+        fold_block_children(self, block)
+    }
+    /// Transform an inline node, by default folding its children.
+    fn fold_inline(&mut self, inline: Inline) -> Option<Inline> {
+        fold_inline_children(self, inline)
+    }
+}
+
+/// Fold the children of `block`, keeping the block itself.
+pub fn fold_block_children<F: Fold + ?Sized>(folder: &mut F, block: Block) -> Option<Block> {
+    let mut blocks = |blocks: Vec<Block>| -> Vec<Block> {
+        blocks
+            .into_iter()
+            .filter_map(|b| folder.fold_block(b))
+            .collect()
+    };
+    Some(match block {
+        Block::Paragraph(content) => Block::Paragraph(fold_inlines(folder, content)),
+        Block::Heading { level, content, id } => Block::Heading {
+            level,
+            content: fold_inlines(folder, content),
+            id,
+        },
+        Block::Blockquote(inner) => Block::Blockquote(blocks(inner)),
+        Block::BlockList(inner) => Block::BlockList(blocks(inner)),
+        Block::List { ordered, items } => Block::List {
+            ordered,
+            items: blocks(items),
+        },
+        Block::FootnoteDefinition { label, content } => Block::FootnoteDefinition {
+            label,
+            content: blocks(content),
+        },
+        Block::Callout {
+            kind,
+            title,
+            content,
+        } => Block::Callout {
+            kind,
+            title,
+            content: blocks(content),
+        },
+        Block::TaskList { items } => Block::TaskList {
+            items: items
+                .into_iter()
+                .filter_map(|(checked, b)| folder.fold_block(b).map(|b| (checked, b)))
+                .collect(),
+        },
+        Block::DefinitionList(items) => Block::DefinitionList(
+            items
+                .into_iter()
+                .map(|(term, definition)| {
+                    let definition = definition
+                        .into_iter()
+                        .filter_map(|b| folder.fold_block(b))
+                        .collect();
+                    (fold_inlines(folder, term), definition)
+                })
+                .collect(),
+        ),
+        Block::Details {
+            summary,
+            content,
+            open,
+        } => Block::Details {
+            summary: fold_inlines(folder, summary),
+            content: content
+                .into_iter()
+                .filter_map(|b| folder.fold_block(b))
+                .collect(),
+            open,
+        },
+        Block::Table {
+            headers,
+            rows,
+            alignments,
+            footer,
+            caption,
+        } => {
+            let mut cells = |cells: Vec<Cell>| -> Vec<Cell> {
+                cells
+                    .into_iter()
+                    .map(|cell| Cell {
+                        content: fold_inlines(folder, cell.content),
+                        ..cell
+                    })
+                    .collect()
+            };
+            Block::Table {
+                headers: cells(headers),
+                rows: rows.into_iter().map(&mut cells).collect(),
+                alignments,
+                footer: footer.into_iter().map(&mut cells).collect(),
+                caption,
+            }
+        }
+        block @ (Block::CodeBlock { .. } | Block::Image { .. } | Block::HorizontalRule) => block,
+    })
+}
+
+/// Fold the children of `inline`, keeping the node itself.
+pub fn fold_inline_children<F: Fold + ?Sized>(folder: &mut F, inline: Inline) -> Option<Inline> {
+    Some(match inline {
+        Inline::Bold(content) => Inline::Bold(fold_inlines(folder, content)),
+        Inline::Italic(content) => Inline::Italic(fold_inlines(folder, content)),
+        Inline::Strikethrough(content) => Inline::Strikethrough(fold_inlines(folder, content)),
+        Inline::Link { text, url } => Inline::Link {
+            text: fold_inlines(folder, text),
+            url,
+        },
+        inline @ (Inline::Text(_)
+        | Inline::Code(_)
+        | Inline::Image { .. }
+        | Inline::LineBreak
+        | Inline::FootnoteRef(_)) => inline,
+    })
+}
+
+fn fold_inlines<F: Fold + ?Sized>(folder: &mut F, inlines: Vec<Inline>) -> Vec<Inline> {
+    inlines
+        .into_iter()
+        .filter_map(|i| folder.fold_inline(i))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_visitors_reach_every_node() {
+        struct Texts(Vec<String>);
+        impl Visitor for Texts {
+            fn visit_inline(&mut self, inline: &Inline) {
+                if let Inline::Text(text) = inline {
+                    self.0.push(text.clone());
+                }
+                walk_inline(self, inline);
+            }
+        }
+
+        let mut doc = Block::BlockList(vec![
+            h1(vec![bold("a")]),
+            quote(ul(["b", "c"])),
+            task_list([(true, p(vec![link(vec![italic("d")], "url")]))]),
+            table(("e", "f"), [(strikethrough("g"), "h")]),
+        ]);
+        let mut texts = Texts(Vec::new());
+        texts.visit_block(&doc);
+        assert_eq!(texts.0, ["a", "b", "c", "d", "e", "f", "g", "h"]);
+
+        struct Upper;
+        impl VisitorMut for Upper {
+            fn visit_inline_mut(&mut self, inline: &mut Inline) {
+                if let Inline::Text(text) = inline {
+                    *text = text.to_uppercase();
+                }
+                walk_inline_mut(self, inline);
+            }
+        }
+        Upper.visit_block_mut(&mut doc);
+        let mut texts = Texts(Vec::new());
+        texts.visit_block(&doc);
+        assert_eq!(texts.0, ["A", "B", "C", "D", "E", "F", "G", "H"]);
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let inlines = [
+            text("Use "),
+            code("toc()"),
+            bold(vec![text(" now"), Inline::LineBreak]),
+            link(vec![italic("docs")], "url"),
+            Inline::Image {
+                alt: " logo".into(),
+                url: "logo.png".into(),
+            },
+            Inline::FootnoteRef("1".into()),
+        ];
+        assert_eq!(plain_text(&inlines), "Use toc() now docs logo");
+    }
+
+    #[test]
+    fn test_fold_removes_nodes() {
+        struct DropStruck;
+        impl Fold for DropStruck {
+            fn fold_block(&mut self, block: Block) -> Option<Block> {
+                match block {
+                    Block::HorizontalRule => None,
+                    block => fold_block_children(self, block),
+                }
+            }
+            fn fold_inline(&mut self, inline: Inline) -> Option<Inline> {
+                match inline {
+                    Inline::Strikethrough(_) => None,
+                    inline => fold_inline_children(self, inline),
+                }
+            }
+        }
+
+        let doc = Block::BlockList(vec![
+            p(("keep ", strikethrough("drop"))),
+            hr(),
+            ul(hr()),
+            table(("a", strikethrough("b")), [("c", "d")]),
+        ]);
+        assert_eq!(
+            DropStruck.fold_block(doc),
+            Some(Block::BlockList(vec![
+                p("keep "),
+                ul(Vec::<Block>::new()),
+                table(("a", ""), [("c", "d")]),
+            ]))
+        );
+    }
+}
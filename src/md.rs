@@ -5,15 +5,16 @@
 //!
 //! # Examples
 //! ```rust
-//! use docloom::md::{FenceStyle, ListMarker, Style, doc};
+//! use docloom::md::{DefinitionLists, FenceStyle, HeadingIds, ListMarker, Style, doc};
 //! use docloom::prelude::*;
 //!
 //! // Optional style configuration
-//! let style = Style {
-//!     code_fence: FenceStyle::Tilde,
-//!     list_marker: ListMarker::Asterisk,
-//!     max_heading: 3,
-//! };
+//! let style = Style::default()
+//!     .code_fence(FenceStyle::Tilde)
+//!     .list_marker(ListMarker::Asterisk)
+//!     .max_heading(3)
+//!     .heading_ids(HeadingIds::Attribute)
+//!     .definition_lists(DefinitionLists::Html);
 //!
 //! let rendered = doc([
 //!     h1("Docloom"),
@@ -25,14 +26,22 @@
 //! ```
 
 use itemize::IntoItems;
-use std::fmt;
+use std::{fmt, io};
 
-use super::{Alignment, Block, Inline, Render, Renderable};
+use super::{
+    Alignment, Block, CalloutKind, Cell, CodeMeta, Inline, IoWriter, Numbering, Render, Renderable,
+    Toc, footnote, stream, table, width,
+};
+
+mod parse;
+
+pub use parse::parse;
 
 /// Markdown document wrapper that renders blocks with a [`Style`].
 pub struct Doc {
     content: Vec<Block>,
     style: Style,
+    toc: Option<Toc>,
 }
 
 impl Doc {
@@ -41,6 +50,7 @@ impl Doc {
         Self {
             content: value.into_items().collect(),
             style: Style::default(),
+            toc: None,
         }
     }
 
@@ -49,12 +59,54 @@ impl Doc {
         self.style = style;
         self
     }
+
+    /// Stream the document to an [`io::Write`] target such as a file or
+    /// stdout, without building an intermediate string.
+    pub fn write_to(&self, writer: impl io::Write) -> io::Result<()> {
+        stream::write_to(writer, |out| self.render(out))
+    }
+
+    /// Number headings hierarchically before rendering. Any table of
+    /// contents lists the numbered headings.
+    pub fn with_numbering(mut self, numbering: Numbering) -> Self {
+        numbering.apply(&mut self.content);
+        self
+    }
+
+    /// Insert a table of contents linking to each heading's anchor.
+    ///
+    /// Accepts a [`crate::TocPosition`] or a configured [`Toc`].
+    pub fn with_toc(mut self, toc: impl Into<Toc>) -> Self {
+        self.toc = Some(toc.into());
+        self
+    }
+}
+
+impl Doc {
+    fn render(&self, writer: &mut impl fmt::Write) -> fmt::Result {
+        let mut renderer = Renderer::with_style(writer, self.style);
+        let inserted;
+        let content = match &self.toc {
+            Some(toc) => {
+                inserted = toc.insert(&self.content);
+                &inserted
+            }
+            None => &self.content,
+        };
+        // Footnotes are numbered and their definitions listed at the end
+        match footnote::collect(content) {
+            Some((body, notes)) => {
+                body.render_with(&mut renderer)?;
+                notes.render_with(&mut renderer)
+            }
+            None => content.render_with(&mut renderer),
+        }
+    }
 }
 
 impl fmt::Display for Doc {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.content
-            .render_with(&mut Renderer::with_style(f, self.style))
+        self.render(f)
     }
 }
 
@@ -64,7 +116,11 @@ pub fn doc(value: impl IntoItems<Block>) -> Doc {
 }
 
 /// Configuration values that affect Markdown output.
+///
+/// Start from [`Style::default`] and adjust it with the builder methods, so
+/// new options can be added without breaking existing code.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Style {
     /// Fence style to use when rendering code blocks.
     pub code_fence: FenceStyle,
@@ -72,6 +128,10 @@ pub struct Style {
     pub list_marker: ListMarker,
     /// Maximum heading level emitted when rendering blocks.
     pub max_heading: u8,
+    /// How explicit heading ids are written.
+    pub heading_ids: HeadingIds,
+    /// Syntax used for definition lists.
+    pub definition_lists: DefinitionLists,
 }
 
 impl Default for Style {
@@ -80,10 +140,44 @@ impl Default for Style {
             code_fence: FenceStyle::Backtick,
             list_marker: ListMarker::Dash,
             max_heading: 6,
+            heading_ids: HeadingIds::Anchor,
+            definition_lists: DefinitionLists::Colon,
         }
     }
 }
 
+impl Style {
+    /// Set the fence used for code blocks.
+    pub fn code_fence(mut self, code_fence: FenceStyle) -> Self {
+        self.code_fence = code_fence;
+        self
+    }
+
+    /// Set the bullet marker used for unordered lists.
+    pub fn list_marker(mut self, list_marker: ListMarker) -> Self {
+        self.list_marker = list_marker;
+        self
+    }
+
+    /// Clamp heading levels to at most `max_heading`.
+    pub fn max_heading(mut self, max_heading: u8) -> Self {
+        self.max_heading = max_heading;
+        self
+    }
+
+    /// Set how explicit heading ids are written.
+    pub fn heading_ids(mut self, heading_ids: HeadingIds) -> Self {
+        self.heading_ids = heading_ids;
+        self
+    }
+
+    /// Set the syntax used for definition lists.
+    pub fn definition_lists(mut self, definition_lists: DefinitionLists) -> Self {
+        self.definition_lists = definition_lists;
+        self
+    }
+}
+
 /// Fence marker options for code blocks.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FenceStyle {
@@ -102,10 +196,39 @@ pub enum ListMarker {
     Dash,
 }
 
+/// Output options for explicit heading ids set with [`Block::with_id`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeadingIds {
+    /// Leave ids out and rely on anchors derived from the heading text, so
+    /// links to explicit ids only work where the viewer reads them itself.
+    Omit,
+    /// Prefix the heading text with an HTML anchor: `## <a id="id"></a>Title`.
+    /// This is the default.
+    Anchor,
+    /// Append a Pandoc-style attribute: `## Title {#id}`. Ids containing
+    /// whitespace or `}` are written as an anchor instead.
+    Attribute,
+}
+
+/// Output options for [`Block::DefinitionList`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefinitionLists {
+    /// Follow each term with `:   definition` lines, as understood by PHP
+    /// Markdown Extra and Pandoc.
+    Colon,
+    /// Write a `<dl>` element for renderers without definition list support,
+    /// such as GitHub.
+    Html,
+}
+
 /// Renderer that writes Markdown to any [`fmt::Write`] target.
 pub struct Renderer<'a, W> {
     writer: &'a mut W,
     style: Style,
+    /// Whether the next inline starts a new line of block content.
+    line_start: bool,
+    /// Whether inline content is being written inside a table cell.
+    in_table: bool,
 }
 
 impl<'a, W> Renderer<'a, W> {
@@ -116,7 +239,12 @@ impl<'a, W> Renderer<'a, W> {
 
     /// Create a renderer with a custom [`Style`].
     pub fn with_style(writer: &'a mut W, style: Style) -> Self {
-        Self { writer, style }
+        Self {
+            writer,
+            style,
+            line_start: true,
+            in_table: false,
+        }
     }
 
     /// Render an arbitrary [`crate::Renderable`] value to the writer.
@@ -128,6 +256,27 @@ impl<'a, W> Renderer<'a, W> {
     }
 }
 
+impl<'a, W: io::Write> Renderer<'a, IoWriter<W>> {
+    /// Create a renderer that streams to the [`io::Write`] target in `writer`.
+    pub fn from_io(writer: &'a mut IoWriter<W>) -> Self {
+        Self::new(writer)
+    }
+
+    /// Create a streaming renderer with a custom [`Style`].
+    pub fn from_io_with_style(writer: &'a mut IoWriter<W>, style: Style) -> Self {
+        Self::with_style(writer, style)
+    }
+
+    /// Render a value, returning any error from the underlying writer.
+    pub fn render_io<R>(&mut self, r: &R) -> io::Result<()>
+    where
+        R: for<'b> Renderable<Renderer<'b, IoWriter<W>>, Output = Result<(), fmt::Error>> + ?Sized,
+    {
+        let result = r.render_with(self);
+        self.writer.check(result)
+    }
+}
+
 impl Renderer<'_, String> {
     /// Render a value to a [`String`] using the default [`Style`].
     pub fn to_string<R>(r: &R) -> String
@@ -172,46 +321,83 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
         use Block::*;
         match inner {
             Paragraph(inner) => {
+                self.line_start = true;
                 inner.render_with(self)?;
+                writeln!(self.writer)?;
                 writeln!(self.writer)
             }
-            Heading { level, content } => {
+            Heading { level, content, id } => {
                 // Apply max_heading style
-                let clamped_level = (*level).min(self.style.max_heading);
+                let clamped_level = (*level).clamp(1, self.style.max_heading.clamp(1, 6));
                 write!(self.writer, "{} ", "#".repeat(clamped_level as usize))?;
-                content.render_with(self)?;
+                // Ids that cannot be written as an attribute get an anchor
+                let (anchor, attribute) = match (id, self.style.heading_ids) {
+                    (Some(id), HeadingIds::Attribute)
+                        if id.contains(|c: char| c.is_whitespace() || c == '}') =>
+                    {
+                        (Some(id), None)
+                    }
+                    (Some(id), HeadingIds::Attribute) => (None, Some(id)),
+                    (Some(id), HeadingIds::Anchor) => (Some(id), None),
+                    _ => (None, None),
+                };
+                if let Some(id) = anchor {
+                    let id = id.replace('&', "&amp;").replace('"', "&quot;");
+                    write!(self.writer, "<a id=\"{id}\"></a>")?;
+                }
+                let mut text = String::new();
+                let mut renderer = Renderer::with_style(&mut text, self.style);
+                renderer.line_start = false;
+                content.render_with(&mut renderer)?;
+                // A trailing run of `#` would be read as a closing sequence
+                let rest = text.trim_end_matches('#');
+                if rest.len() < text.len() && (rest.is_empty() || rest.ends_with(' ')) {
+                    text.insert(rest.len(), '\\');
+                }
+                self.writer.write_str(&text)?;
+                if let Some(id) = attribute {
+                    write!(self.writer, " {{#{id}}}")?;
+                }
+                writeln!(self.writer)?;
                 writeln!(self.writer)
             }
-            CodeBlock { language, content } => {
-                // Apply code_fence style
-                let fence = match self.style.code_fence {
-                    FenceStyle::Backtick => "```",
-                    FenceStyle::Tilde => "~~~",
+            CodeBlock {
+                language,
+                content,
+                meta,
+            } => {
+                // Apply code_fence style, outgrowing any fence inside the content
+                let marker = match self.style.code_fence {
+                    FenceStyle::Backtick => '`',
+                    FenceStyle::Tilde => '~',
                 };
+                let fence = marker
+                    .to_string()
+                    .repeat(longest_run(content, marker).max(2) + 1);
 
+                write!(self.writer, "{}", fence)?;
                 if let Some(lang) = language {
-                    writeln!(self.writer, "{}{}", fence, lang)?;
-                } else {
-                    writeln!(self.writer, "{}", fence)?;
+                    write!(self.writer, "{}", lang)?;
                 }
+                writeln!(self.writer, "{}", InfoAttributes(meta))?;
                 writeln!(self.writer, "{content}")?;
                 writeln!(self.writer, "{}", fence)?;
                 writeln!(self.writer)
             }
             List { ordered, items } => {
                 for (idx, item) in items.iter().enumerate() {
-                    if *ordered {
-                        write!(self.writer, "{}. ", idx + 1)?;
+                    let marker = if *ordered {
+                        format!("{}. ", idx + 1)
                     } else {
                         // Apply list_marker style
-                        let marker = match self.style.list_marker {
-                            ListMarker::Asterisk => "*",
-                            ListMarker::Dash => "-",
-                        };
-                        write!(self.writer, "{} ", marker)?;
-                    }
-                    item.render_with(self)?;
-                    writeln!(self.writer)?;
+                        match self.style.list_marker {
+                            ListMarker::Asterisk => "* ",
+                            ListMarker::Dash => "- ",
+                        }
+                        .to_string()
+                    };
+                    write!(self.writer, "{marker}")?;
+                    self.render_item(marker.len(), item)?;
                 }
                 writeln!(self.writer)
             }
@@ -219,8 +405,9 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 for (checked, item) in items.iter() {
                     let mark = if *checked { "x" } else { " " };
                     write!(self.writer, "- [{mark}] ")?;
-                    item.render_with(self)?;
-                    writeln!(self.writer)?;
+                    // The checkbox belongs to the item text, so continuation
+                    // lines only need to clear the `- ` marker
+                    self.render_item(2, item)?;
                 }
                 writeln!(self.writer)
             }
@@ -228,14 +415,38 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 headers,
                 rows,
                 alignments,
+                footer,
+                caption,
             } => {
-                let col_count = headers.len();
-                let mut widths: Vec<usize> =
-                    headers.iter().map(|h| Self::measure_inline(h)).collect();
+                // Render cells up front so widths account for escaping. GFM
+                // has no spans or footers, so spanned positions stay empty
+                // and footer rows follow the body.
+                let columns = table::column_count(headers);
+                let render_rows = |rows: &[Vec<Cell>]| {
+                    let grid = table::layout(rows, columns);
+                    grid.iter()
+                        .enumerate()
+                        .map(|(r, row)| {
+                            row.iter()
+                                .enumerate()
+                                .map(|(c, slot)| match slot {
+                                    Some(placed) if placed.starts_at(r, c) => {
+                                        self.render_cell(&placed.cell.content)
+                                    }
+                                    _ => Ok(String::new()),
+                                })
+                                .collect::<Result<Vec<_>, _>>()
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                };
+                let headers = render_rows(std::slice::from_ref(headers))?.remove(0);
+                let mut rows = render_rows(rows)?;
+                rows.extend(render_rows(footer)?);
 
-                for row in rows {
-                    for (i, cell) in row.iter().enumerate().take(col_count) {
-                        widths[i] = widths[i].max(Self::measure_inline(cell));
+                let mut widths: Vec<usize> = headers.iter().map(|h| width::width(h)).collect();
+                for row in &rows {
+                    for (i, cell) in row.iter().enumerate() {
+                        widths[i] = widths[i].max(width::width(cell));
                     }
                 }
 
@@ -247,16 +458,18 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 // header row
                 write!(self.writer, "|")?;
                 for (i, h) in headers.iter().enumerate() {
-                    // Render to a temporary string first
-                    let rendered = Renderer::to_string(h);
-                    write!(self.writer, " {:width$} |", rendered, width = widths[i])?;
+                    write!(
+                        self.writer,
+                        " {} |",
+                        width::pad(h, widths[i], Alignment::Left)
+                    )?;
                 }
                 writeln!(self.writer)?;
 
                 // separator row with alignment
                 write!(self.writer, "|")?;
-                for (w, align) in widths.iter().zip(alignments.iter()) {
-                    let spec = match align {
+                for (i, w) in widths.iter().enumerate() {
+                    let spec = match alignments.get(i).unwrap_or(&Alignment::Left) {
                         Alignment::Left => {
                             let dashes = "-".repeat((*w).saturating_sub(1));
                             format!(":{dashes}")
@@ -275,60 +488,125 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 writeln!(self.writer)?;
 
                 // body rows
-                for row in rows {
+                for row in &rows {
                     write!(self.writer, "|")?;
-                    for (i, w) in widths.iter().enumerate() {
-                        if let Some(cell) = row.get(i) {
-                            let rendered = Renderer::to_string(cell);
-                            write!(self.writer, " {:width$} |", rendered, width = *w)?;
-                        } else {
-                            // Empty cell if row doesn't have enough columns
-                            write!(self.writer, " {:width$} |", "", width = *w)?;
-                        }
+                    for (cell, w) in row.iter().zip(&widths) {
+                        write!(self.writer, " {} |", width::pad(cell, *w, Alignment::Left))?;
                     }
                     writeln!(self.writer)?;
                 }
+                writeln!(self.writer)?;
 
-                writeln!(self.writer)
+                // Pandoc's caption syntax, read as a plain paragraph elsewhere
+                if let Some(caption) = caption {
+                    writeln!(
+                        self.writer,
+                        "Table: {}",
+                        Escape::text(caption, false, false)
+                    )?;
+                    writeln!(self.writer)?;
+                }
+                Ok(())
             }
             Blockquote(inner) => {
-                // Render each block individually and add to blockquote
-                for (i, block) in inner.iter().enumerate() {
-                    // Render this block to a string
-                    let mut block_content = String::new();
-                    block.render_with(&mut Renderer::with_style(&mut block_content, self.style))?;
-
-                    // Remove trailing newlines from the block content
-                    let block_content = block_content.trim_end();
-
-                    // Add "> " prefix to each line of this block
-                    for line in block_content.lines() {
-                        writeln!(self.writer, "> {}", line)?;
-                    }
-
-                    // Add a blank blockquote line between blocks (except after the last one)
-                    if i < inner.len() - 1 {
-                        writeln!(self.writer, ">")?;
-                    }
+                self.render_quoted(inner)?;
+                writeln!(self.writer)
+            }
+            Callout {
+                kind,
+                title,
+                content,
+            } => {
+                // GitHub alert syntax, with any title on the marker line
+                let marker = match kind {
+                    CalloutKind::Danger => "CAUTION",
+                    kind => &kind.name().to_uppercase(),
+                };
+                write!(self.writer, "> [!{marker}]")?;
+                if let Some(title) = title {
+                    // The marker line holds the whole title
+                    let title = title.replace(['\r', '\n'], " ");
+                    write!(self.writer, " {}", Escape::text(&title, false, false))?;
                 }
-                Ok(())
+                writeln!(self.writer)?;
+                self.render_quoted(content)?;
+                writeln!(self.writer)
+            }
+            Image { alt, url } => {
+                writeln!(
+                    self.writer,
+                    "![{}]({})",
+                    Escape::text(alt, false, false),
+                    Destination(url)
+                )?;
+                writeln!(self.writer)
+            }
+            HorizontalRule => {
+                writeln!(self.writer, "---")?;
+                writeln!(self.writer)
             }
-            Image { alt, url } => writeln!(self.writer, "![{alt}]({url})"),
-            HorizontalRule => writeln!(self.writer, "---"),
             BlockList(inner) => {
                 for block in inner.iter() {
                     block.render_with(self)?;
                 }
                 Ok(())
             }
+            FootnoteDefinition { label, content } => {
+                // Continuation lines are indented four columns, as in GFM
+                write!(self.writer, "[^{label}]: ")?;
+                self.render_blocks(4, content)?;
+                writeln!(self.writer)
+            }
+            DefinitionList(items) => {
+                if self.style.definition_lists == DefinitionLists::Html {
+                    let html = crate::html::Renderer::to_string(inner);
+                    return writeln!(self.writer, "{html}");
+                }
+                for (i, (term, definition)) in items.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(self.writer)?;
+                    }
+                    self.line_start = true;
+                    term.render_with(self)?;
+                    // The marker is padded so definitions start in column four,
+                    // where their continuation lines are indented
+                    write!(self.writer, "\n:   ")?;
+                    self.render_blocks(4, definition)?;
+                }
+                writeln!(self.writer)
+            }
+            Details {
+                summary,
+                content,
+                open,
+            } => {
+                // GitHub renders Markdown between the blank lines of the HTML
+                let open = if *open { " open" } else { "" };
+                let summary = crate::html::Renderer::to_string(summary.as_slice());
+                writeln!(self.writer, "<details{open}>")?;
+                writeln!(self.writer, "<summary>{summary}</summary>")?;
+                writeln!(self.writer)?;
+                content.render_with(self)?;
+                writeln!(self.writer, "</details>")?;
+                writeln!(self.writer)
+            }
         }
     }
 
     /// Render an [`crate::Inline`] into Markdown.
     fn render_inline(&mut self, inner: &Inline) -> fmt::Result {
         use Inline::*;
+        let line_start = std::mem::replace(&mut self.line_start, false);
         match inner {
-            Text(text) => write!(self.writer, "{text}"),
+            Text(text) => {
+                write!(
+                    self.writer,
+                    "{}",
+                    Escape::text(text, line_start, self.in_table)
+                )?;
+                self.line_start = text.ends_with('\n') || (line_start && text.is_empty());
+                Ok(())
+            }
             Bold(inner) => {
                 write!(self.writer, "**")?;
                 inner.render_with(self)?;
@@ -347,36 +625,285 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 write!(self.writer, "~~")?;
                 Ok(())
             }
-            Code(text) => write!(self.writer, "`{text}`"),
+            Code(text) => self.write_code_span(text),
             Link { text, url } => {
                 write!(self.writer, "[")?;
                 text.render_with(self)?;
-                write!(self.writer, "]({url})")?;
+                write!(self.writer, "]({})", Destination(url))?;
                 Ok(())
             }
-            Image { alt, url } => writeln!(self.writer, "![{alt}]({url})"),
-            LineBreak => writeln!(self.writer, "  "),
+            Image { alt, url } => write!(
+                self.writer,
+                "![{}]({})",
+                Escape::text(alt, false, self.in_table),
+                Destination(url)
+            ),
+            // Table rows cannot span lines, so cells break with HTML
+            LineBreak if self.in_table => write!(self.writer, "<br>"),
+            LineBreak => {
+                self.line_start = true;
+                writeln!(self.writer, "  ")
+            }
+            FootnoteRef(label) => write!(self.writer, "[^{label}]"),
         }
     }
 }
 
 impl<'a, W: fmt::Write> Renderer<'a, W> {
-    fn measure_inline(inline: &Inline) -> usize {
-        match inline {
-            Inline::Text(t) => t.to_string().chars().count(),
-            Inline::Bold(content) | Inline::Italic(content) => {
-                content.iter().map(Self::measure_inline).sum::<usize>() + 4
+    /// Render a list item on the marker line, indenting continuation lines
+    /// by `indent` columns so nested blocks stay inside the item.
+    fn render_item(&mut self, indent: usize, item: &Block) -> fmt::Result {
+        match item {
+            Block::BlockList(blocks) => self.render_blocks(indent, blocks),
+            block => self.render_blocks(indent, std::slice::from_ref(block)),
+        }
+    }
+
+    /// Render blocks after a marker, indenting continuation lines by
+    /// `indent` columns.
+    fn render_blocks(&mut self, indent: usize, blocks: &[Block]) -> fmt::Result {
+        let mut content = String::new();
+        for (i, block) in blocks.iter().enumerate() {
+            if i > 0 {
+                // A sublist directly after the item text keeps the list tight
+                let tight = matches!(blocks[i - 1], Block::Paragraph(_))
+                    && matches!(block, Block::List { .. } | Block::TaskList { .. });
+                content.push_str(if tight { "\n" } else { "\n\n" });
             }
-            Inline::Strikethrough(content) => {
-                content.iter().map(Self::measure_inline).sum::<usize>() + 4
+            let mut rendered = String::new();
+            block.render_with(&mut Renderer::with_style(&mut rendered, self.style))?;
+            content.push_str(rendered.trim_end());
+        }
+        for (i, line) in content.split('\n').enumerate() {
+            if i > 0 && !line.is_empty() {
+                write!(self.writer, "{:indent$}", "")?;
             }
-            Inline::Code(t) => t.to_string().chars().count() + 2,
-            Inline::Link { text, .. } => 2 + text.iter().map(Self::measure_inline).sum::<usize>(),
-            Inline::Image { alt, url } => {
-                5 + alt.to_string().chars().count() + url.to_string().chars().count()
+            writeln!(self.writer, "{line}")?;
+        }
+        Ok(())
+    }
+
+    /// Render blocks prefixed with `> `, separated by blank quoted lines.
+    fn render_quoted<'b>(&mut self, blocks: impl IntoIterator<Item = &'b Block>) -> fmt::Result {
+        for (i, block) in blocks.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(self.writer, ">")?;
+            }
+            let mut rendered = String::new();
+            block.render_with(&mut Renderer::with_style(&mut rendered, self.style))?;
+            for line in rendered.trim_end().lines() {
+                writeln!(self.writer, "> {line}")?;
             }
-            Inline::LineBreak => 0,
         }
+        Ok(())
+    }
+
+    /// Render a table cell to a string with pipes and newlines escaped.
+    fn render_cell(&self, cell: &[Inline]) -> Result<String, fmt::Error> {
+        let mut buf = String::new();
+        let mut renderer = Renderer::with_style(&mut buf, self.style);
+        renderer.line_start = false;
+        renderer.in_table = true;
+        cell.render_with(&mut renderer)?;
+        Ok(buf)
+    }
+
+    /// Write a code span fenced by more backticks than the content contains.
+    fn write_code_span(&mut self, text: &str) -> fmt::Result {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let text = if self.in_table {
+            text.replace('|', "\\|").replace('\n', " ")
+        } else {
+            text.to_string()
+        };
+        let fence = "`".repeat(longest_run(&text, '`') + 1);
+        // Pad so that edge backticks and edge spaces survive normalization
+        let pad = text.starts_with('`')
+            || text.ends_with('`')
+            || (text.starts_with(' ') && text.ends_with(' ') && text.trim() != "");
+        let pad = if pad { " " } else { "" };
+        write!(self.writer, "{fence}{pad}{text}{pad}{fence}")
+    }
+}
+
+/// Code block metadata written after the language of a fence, as in
+/// `title="main.rs" showLineNumbers=40 {3-5,8}`.
+struct InfoAttributes<'a>(&'a CodeMeta);
+
+impl fmt::Display for InfoAttributes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let meta = self.0;
+        if let Some(title) = &meta.title {
+            let title = title.replace('\\', "\\\\").replace('"', "\\\"");
+            write!(f, " title=\"{title}\"")?;
+        }
+        match meta.start_line {
+            Some(1) => write!(f, " showLineNumbers")?,
+            Some(start) => write!(f, " showLineNumbers={start}")?,
+            None => {}
+        }
+        if !meta.highlighted.is_empty() {
+            write!(f, " {{{}}}", meta.highlighted_ranges())?;
+        }
+        Ok(())
+    }
+}
+
+/// Length of the longest run of `marker` characters in `text`.
+fn longest_run(text: &str, marker: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        current = if c == marker { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+    longest
+}
+
+/// Display adapter that escapes text so it renders literally.
+struct Escape<'a> {
+    value: &'a str,
+    line_start: bool,
+    in_table: bool,
+}
+
+impl<'a> Escape<'a> {
+    /// Escape inline text, including block markers when it starts a line.
+    fn text(value: &'a str, line_start: bool, in_table: bool) -> Self {
+        Self {
+            value,
+            line_start,
+            in_table,
+        }
+    }
+}
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut line_start = self.line_start;
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let prev = i.checked_sub(1).map(|j| chars[j]);
+            let next = chars.get(i + 1).copied();
+
+            if line_start {
+                line_start = false;
+                // Block markers only take effect at the start of a line
+                match c {
+                    // Leading whitespace would be stripped or start a code
+                    // block, so the first character is written as a reference
+                    ' ' | '\t' => {
+                        write!(f, "&#{};", c as u32)?;
+                        i += 1;
+                        continue;
+                    }
+                    '#' | '>' | '-' | '+' | '=' => {
+                        write!(f, "\\{c}")?;
+                        i += 1;
+                        continue;
+                    }
+                    // A definition list marker
+                    ':' if next.is_none_or(char::is_whitespace) => {
+                        f.write_str("\\:")?;
+                        i += 1;
+                        continue;
+                    }
+                    '0'..='9' => {
+                        let digits = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+                        for digit in &chars[i..i + digits] {
+                            f.write_char(*digit)?;
+                        }
+                        i += digits;
+                        if let Some(delim @ ('.' | ')')) = chars.get(i) {
+                            write!(f, "\\{delim}")?;
+                            i += 1;
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            match c {
+                '\\' | '`' | '*' | '[' | ']' | '~' => write!(f, "\\{c}")?,
+                '|' if self.in_table => f.write_str("\\|")?,
+                '\n' if self.in_table => f.write_char(' ')?,
+                // Spaces before a line ending are dropped or form a line break
+                ' ' if next == Some('\n') => f.write_str("&#32;")?,
+                '\n' => {
+                    f.write_char('\n')?;
+                    line_start = true;
+                }
+                // Intraword underscores never form emphasis
+                '_' if prev.is_some_and(char::is_alphanumeric)
+                    && next.is_some_and(char::is_alphanumeric) =>
+                {
+                    f.write_char('_')?
+                }
+                '_' => f.write_str("\\_")?,
+                '<' if next.is_some_and(|n| n.is_ascii_alphabetic() || "/!?".contains(n)) => {
+                    f.write_str("\\<")?
+                }
+                '&' if is_entity_like(&chars[i + 1..]) => f.write_str("\\&")?,
+                c => f.write_char(c)?,
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the characters following `&` would be read as an entity reference.
+fn is_entity_like(rest: &[char]) -> bool {
+    let name = rest
+        .iter()
+        .take_while(|c| c.is_ascii_alphanumeric() || **c == '#')
+        .count();
+    name > 0 && rest.get(name) == Some(&';')
+}
+
+/// Display adapter that writes a link destination, using `<...>` when needed.
+struct Destination<'a>(&'a str);
+
+impl fmt::Display for Destination<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+        let url = self.0;
+        let mut depth: i32 = 0;
+        let balanced = url.chars().all(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth >= 0
+        }) && depth == 0;
+        let pointy = url.is_empty()
+            || !balanced
+            || url
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control() || c == '<' || c == '>');
+
+        if pointy {
+            f.write_char('<')?;
+        }
+        let chars: Vec<char> = url.chars().collect();
+        for (i, c) in chars.iter().enumerate() {
+            match c {
+                '\\' | '<' | '>' => write!(f, "\\{c}")?,
+                '&' if is_entity_like(&chars[i + 1..]) => f.write_str("\\&")?,
+                c => f.write_char(*c)?,
+            }
+        }
+        if pointy {
+            f.write_char('>')?;
+        }
+        Ok(())
     }
 }
 
@@ -422,6 +949,269 @@ mod tests {
         assert!(separator.contains("----:")); // right alignment
     }
 
+    #[test]
+    fn test_markdown_table_wide_characters() {
+        let table = table(
+            ("Word", "Note"),
+            (("日本語", "cjk"), ("👩‍💻", "zwj"), ("e\u{301}", "combining")),
+        );
+        let markdown = Renderer::to_string(&table);
+        assert_eq!(
+            markdown.trim(),
+            r#"
+| Word   | Note      |
+| :----- | :-------- |
+| 日本語 | cjk       |
+| 👩‍💻     | zwj       |
+| é      | combining |
+            "#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_markdown_table_spans() {
+        let table = table(
+            ("Region", cell("Sales").with_colspan(2)),
+            [
+                vec![cell("North").with_rowspan(2), cell("Q1"), cell("10")],
+                vec![cell(("Q2 ", italic("est."))), cell("12")],
+            ],
+        )
+        .with_footer([(cell("Total").with_colspan(2), "22")])
+        .with_caption("Sales | 2024");
+        assert_eq!(
+            Renderer::to_string(&table),
+            "| Region | Sales     |     |\n\
+             | :----- | :-------- | :-- |\n\
+             | North  | Q1        | 10  |\n\
+             |        | Q2 *est.* | 12  |\n\
+             | Total  |           | 22  |\n\
+             \n\
+             Table: Sales | 2024\n\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_nested_list() {
+        let list = ul((
+            Block::BlockList(vec![p("Parent"), ol(["one", "two"])]),
+            Block::BlockList(vec![p("First."), p("Second."), code_block((), "code")]),
+        ));
+        assert_eq!(
+            Renderer::to_string(&list),
+            "- Parent\n  1. one\n  2. two\n- First.\n\n  Second.\n\n  ```\n  code\n  ```\n\n"
+        );
+
+        // Continuation lines clear the widest ordered marker
+        let mut items: Vec<Block> = (1..=10).map(|i| p(i.to_string())).collect();
+        items[9] = Block::BlockList(vec![p("ten"), ul(["nested"])]);
+        let markdown = Renderer::to_string(&ol(items));
+        assert!(markdown.contains("10. ten\n    - nested\n"));
+    }
+
+    #[test]
+    fn test_markdown_toc() {
+        let markdown = doc([h1("Guide"), h2("Install"), h3("From source"), h2("Usage")])
+            .with_toc(crate::TocPosition::AfterTitle)
+            .to_string();
+        assert_eq!(
+            markdown,
+            "# Guide\n\n\
+             - [Install](#install)\n  - [From source](#from-source)\n- [Usage](#usage)\n\n\
+             ## Install\n\n### From source\n\n## Usage\n\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_code_meta() {
+        let snippet = code_block("rust", "let a = 1;")
+            .with_title("main.rs")
+            .with_start_line(3)
+            .with_highlighted([3, 4, 5, 8]);
+        assert_eq!(
+            Renderer::to_string(&snippet),
+            "```rust title=\"main.rs\" showLineNumbers=3 {3-5,8}\nlet a = 1;\n```\n\n"
+        );
+
+        let snippet = code_block((), "x").with_start_line(1);
+        assert_eq!(
+            Renderer::to_string(&snippet),
+            "``` showLineNumbers\nx\n```\n\n"
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "code block metadata set on a table")]
+    fn test_code_meta_on_other_blocks() {
+        let _ = table(("Name",), [("Al",)]).with_title("People");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "footer rows added to a paragraph")]
+    fn test_table_setters_on_other_blocks() {
+        let _ = p("Total: 22").with_footer([("Total", "22")]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "anchor id set on a paragraph")]
+    fn test_id_on_other_blocks() {
+        let _ = p("Setup").with_id("setup");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "open state set on a callout")]
+    fn test_open_on_other_blocks() {
+        let _ = note(p("Details")).with_open(true);
+    }
+
+    #[test]
+    fn test_markdown_footnotes() {
+        let (first, source) = footnote("Source.");
+        let details = Block::FootnoteDefinition {
+            label: "details".into(),
+            content: vec![p("Details."), ul(["a", "b"])],
+        };
+        let blocks = [
+            details,
+            p((
+                "Claim",
+                Inline::FootnoteRef("details".into()),
+                " and another",
+                first,
+                ".",
+            )),
+            source,
+            // Repeating a note reuses its number
+            p(vec![footnote("Source.").0]),
+        ];
+        assert_eq!(
+            doc(blocks).to_string(),
+            "Claim[^1] and another[^2].\n\n[^2]\n\n\
+             [^1]: Details.\n    - a\n    - b\n\n[^2]: Source.\n\n"
+        );
+
+        // Bare renderers keep the labels and position of definitions
+        let note = Block::FootnoteDefinition {
+            label: "src".into(),
+            content: vec![p("Text")],
+        };
+        assert_eq!(Renderer::to_string(&note), "[^src]: Text\n\n");
+    }
+
+    #[test]
+    fn test_markdown_callouts() {
+        let markdown = Renderer::to_string(&warning(p("Disk *almost* full.")));
+        assert_eq!(markdown, "> [!WARNING]\n> Disk \\*almost\\* full.\n\n");
+
+        let callout = danger((p("Data is lost."), ul(["a", "b"]))).with_title("Before *deleting*");
+        assert_eq!(
+            Renderer::to_string(&callout),
+            "> [!CAUTION] Before \\*deleting\\*\n> Data is lost.\n>\n> - a\n> - b\n\n"
+        );
+
+        let callout = note(p("Body")).with_title("Two\nlines");
+        assert_eq!(
+            Renderer::to_string(&callout),
+            "> [!NOTE] Two lines\n> Body\n\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_definition_lists() {
+        let list = dl((
+            (
+                code("--color <WHEN>"),
+                "Colorize output: *always* or never.",
+            ),
+            (
+                "Config",
+                block((p("Read from disk."), code_block("toml", "a = 1"))),
+            ),
+        ));
+        assert_eq!(
+            Renderer::to_string(&list),
+            "`--color <WHEN>`\n:   Colorize output: \\*always\\* or never.\n\n\
+             Config\n:   Read from disk.\n\n    ```toml\n    a = 1\n    ```\n\n"
+        );
+
+        let style = Style {
+            definition_lists: DefinitionLists::Html,
+            ..Style::default()
+        };
+        assert_eq!(
+            Renderer::to_string_with_style(&dl([("A <b>", "x")]), style),
+            "<dl>\n<dt>A &lt;b&gt;</dt>\n<dd>x</dd>\n</dl>\n\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_details() {
+        let section = details(("Log for ", code("a<b")), (p("*Done*"), ul(["a"]))).with_open(true);
+        assert_eq!(
+            Renderer::to_string(&section),
+            "<details open>\n<summary>Log for <code>a&lt;b</code></summary>\n\n\
+             \\*Done\\*\n\n- a\n\n</details>\n\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_heading_ids() {
+        let blocks = [
+            h2("Usage").with_id("usage"),
+            p(vec![xref("See usage", "usage")]),
+        ];
+        let render = |heading_ids| {
+            let style = Style {
+                heading_ids,
+                ..Style::default()
+            };
+            Renderer::to_string_with_style(&blocks[..], style)
+        };
+        assert_eq!(
+            render(HeadingIds::Omit),
+            "## Usage\n\n[See usage](#usage)\n\n"
+        );
+        assert_eq!(
+            render(HeadingIds::Anchor),
+            "## <a id=\"usage\"></a>Usage\n\n[See usage](#usage)\n\n"
+        );
+        assert!(render(HeadingIds::Attribute).starts_with("## Usage {#usage}\n"));
+
+        // Only explicit ids are written
+        let markdown = Renderer::to_string_with_style(
+            &h2("Plain"),
+            Style {
+                heading_ids: HeadingIds::Attribute,
+                ..Style::default()
+            },
+        );
+        assert_eq!(markdown, "## Plain\n\n");
+        let markdown = Renderer::to_string_with_style(
+            &h2("X").with_id("a} b"),
+            Style {
+                heading_ids: HeadingIds::Attribute,
+                ..Style::default()
+            },
+        );
+        assert_eq!(markdown, "## <a id=\"a} b\"></a>X\n\n");
+
+        // Cross-references resolve with the default style
+        let markdown = doc([
+            h1("Guide"),
+            h2("Setup").with_id("install"),
+            p(vec![xref("see", "install")]),
+        ])
+        .with_toc(Toc::new())
+        .to_string();
+        assert!(markdown.contains("[Setup](#install)"));
+        assert!(markdown.contains("## <a id=\"install\"></a>Setup\n"));
+    }
+
     #[test]
     fn test_markdown_strikethrough() {
         // Simple strikethrough
@@ -455,6 +1245,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_markdown_escaping() {
+        let markdown = Renderer::to_string(&p("# 1. *not* [a](b) snake_case _x_ <div> &amp;"));
+        assert_eq!(
+            markdown.trim(),
+            r"\# 1. \*not\* \[a\](b) snake_case \_x\_ \<div> \&amp;"
+        );
+
+        let markdown = Renderer::to_string(&p("2) item\n- dash\n+ plus"));
+        assert_eq!(markdown.trim(), "2\\) item\n\\- dash\n\\+ plus");
+
+        let markdown = Renderer::to_string(&p((code("a ` b"), " ", code("`edge`"))));
+        assert_eq!(markdown.trim(), "``a ` b`` `` `edge` ``");
+
+        let markdown = Renderer::to_string(&p(vec![link("docs", "a b(c")]));
+        assert_eq!(markdown.trim(), "[docs](<a b(c>)");
+
+        let markdown = Renderer::to_string(&table(("a|b", code("x|y")), [("c\nd", "e")]));
+        assert!(markdown.contains("| a\\|b | `x\\|y` |"));
+        assert!(markdown.contains("| c d  | e      |"));
+
+        let markdown = Renderer::to_string(&code_block("md", "```\ncode\n```"));
+        assert!(markdown.starts_with("````md\n```\ncode\n```\n````"));
+    }
+
     #[test]
     fn test_markdown_blockquote() {
         // Simple blockquote with a paragraph
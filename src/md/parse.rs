@@ -0,0 +1,1779 @@
+//! Markdown parser producing [`Block`] and [`Inline`] trees.
+//!
+//! Supports the CommonMark block and inline structure that maps onto the
+//! document model, plus the GFM table, task list and strikethrough
+//! extensions, Pandoc `Table:` captions, footnotes, alerts, definition
+//! lists, `<details>` sections and heading ids written as anchors or
+//! `{#id}` attributes. Constructs without a matching node (other raw HTML,
+//! link reference definitions) are kept as literal text, as is content
+//! nested more than 64 containers or inline elements deep.
+
+use std::collections::HashMap;
+
+use crate::{Alignment, Block, CalloutKind, Cell, CodeMeta, Inline, plain_text};
+
+/// Parse Markdown source into a sequence of [`Block`] nodes.
+///
+/// # Examples
+/// ```rust
+/// use docloom::md::parse;
+/// use docloom::prelude::*;
+///
+/// let blocks = parse("# Changelog\n\n- Added **tables**\n");
+/// assert_eq!(
+///     blocks,
+///     vec![h1("Changelog"), ul([p(("Added ", bold("tables")))])]
+/// );
+/// ```
+pub fn parse(input: &str) -> Vec<Block> {
+    let lines: Vec<String> = input.lines().map(expand_tabs).collect();
+    parse_blocks(&lines, 0)
+}
+
+/// Deepest nesting of containers, or of inline elements within a block.
+/// Deeper content is kept as literal text, so hostile input cannot exhaust
+/// the stack.
+const MAX_DEPTH: usize = 64;
+
+// ---------------- Block Parsing ----------------
+
+fn parse_blocks(lines: &[String], depth: usize) -> Vec<Block> {
+    if depth > MAX_DEPTH {
+        let text = paragraph_text(lines);
+        return match text.is_empty() {
+            true => Vec::new(),
+            false => vec![Block::Paragraph(vec![Inline::Text(text)])],
+        };
+    }
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].as_str();
+        if is_blank(line) {
+            i += 1;
+            continue;
+        }
+
+        let (indent, rest) = split_indent(line);
+        if indent >= 4 {
+            i = parse_indented_code(lines, i, &mut blocks);
+        } else if let Some(fence) = Fence::open(rest, indent) {
+            i = parse_fenced_code(lines, i + 1, fence, &mut blocks);
+        } else if let Some(heading) = atx_heading(rest) {
+            blocks.push(heading);
+            i += 1;
+        } else if is_thematic_break(rest) {
+            blocks.push(Block::HorizontalRule);
+            i += 1;
+        } else if rest.starts_with('>') {
+            i = parse_blockquote(lines, i, depth, &mut blocks);
+        } else if let Some(marker) = ListItem::open(line) {
+            i = parse_list(lines, i, marker, depth, &mut blocks);
+        } else if is_table_start(lines, i) {
+            i = parse_table(lines, i, &mut blocks);
+        } else if let Some(open) = details_open(rest) {
+            i = parse_details(lines, i, open, depth, &mut blocks);
+        } else if let Some((label, first)) = footnote_definition(rest) {
+            i = parse_footnote(lines, i, label, first, depth, &mut blocks);
+        } else if is_definition_start(lines, i) {
+            i = parse_definitions(lines, i, depth, &mut blocks);
+        } else {
+            i = parse_paragraph(lines, i, &mut blocks);
+        }
+    }
+    blocks
+}
+
+fn parse_indented_code(lines: &[String], start: usize, blocks: &mut Vec<Block>) -> usize {
+    let mut end = start;
+    let mut content = Vec::new();
+    while end < lines.len() {
+        let line = lines[end].as_str();
+        if is_blank(line) {
+            content.push("");
+        } else if split_indent(line).0 >= 4 {
+            content.push(&line[4..]);
+        } else {
+            break;
+        }
+        end += 1;
+    }
+    while content.last().is_some_and(|line| is_blank(line)) {
+        content.pop();
+    }
+    blocks.push(Block::CodeBlock {
+        language: None,
+        content: content.join("\n"),
+        meta: CodeMeta::default(),
+    });
+    end
+}
+
+/// An opening code fence and the info it carries.
+struct Fence {
+    marker: char,
+    len: usize,
+    indent: usize,
+    language: Option<String>,
+    meta: CodeMeta,
+}
+
+impl Fence {
+    fn open(rest: &str, indent: usize) -> Option<Self> {
+        let marker = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+        let len = rest.chars().take_while(|c| *c == marker).count();
+        if len < 3 {
+            return None;
+        }
+        let info = rest[len..].trim();
+        if marker == '`' && info.contains('`') {
+            return None;
+        }
+        let (language, meta) = parse_info(info);
+        Some(Self {
+            marker,
+            len,
+            indent,
+            language,
+            meta,
+        })
+    }
+
+    fn closes(&self, line: &str) -> bool {
+        let (indent, rest) = split_indent(line);
+        let len = rest.chars().take_while(|c| *c == self.marker).count();
+        indent < 4 && len >= self.len && rest[len..].trim().is_empty()
+    }
+}
+
+/// Split a fence info string such as `rust title="main.rs" {3-5}` into the
+/// language and code block metadata.
+fn parse_info(info: &str) -> (Option<String>, CodeMeta) {
+    let mut language = None;
+    let mut meta = CodeMeta::default();
+    let mut rest = info.trim_start();
+    let mut first = true;
+    while !rest.is_empty() {
+        // Quoted values may contain spaces
+        let end = if let Some(body) = rest.strip_prefix('{') {
+            body.find('}').map_or(rest.len(), |end| end + 2)
+        } else {
+            match rest.find(['"', ' ', '\t']) {
+                Some(quote) if rest[quote..].starts_with('"') => {
+                    closing_quote(&rest[quote + 1..]).map_or(rest.len(), |end| quote + end + 2)
+                }
+                Some(space) => space,
+                None => rest.len(),
+            }
+        };
+        let word = &rest[..end];
+        rest = rest[end..].trim_start();
+
+        if let Some(lines) = word.strip_prefix('{').and_then(|w| w.strip_suffix('}')) {
+            for range in lines.split(',') {
+                let range = range.trim();
+                let (start, end) = range.split_once('-').unwrap_or((range, range));
+                // Ranges are capped so a stray `{1-999999999}` stays cheap
+                if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                    meta.highlighted
+                        .extend(start..=end.min(start.saturating_add(10_000)));
+                }
+            }
+        } else if let Some(title) = word.strip_prefix("title=") {
+            let title = title.strip_prefix('"').and_then(|t| t.strip_suffix('"'));
+            meta.title = title.map(|t| t.replace("\\\"", "\"").replace("\\\\", "\\"));
+        } else if word == "showLineNumbers" {
+            meta.start_line = Some(1);
+        } else if let Some(start) = word.strip_prefix("showLineNumbers=") {
+            meta.start_line = start.parse().ok();
+        } else if first && !word.contains('=') {
+            language = Some(unescape(word, false));
+        }
+        first = false;
+    }
+    (language, meta)
+}
+
+/// Byte offset of the first unescaped `"` in `text`.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_fenced_code(
+    lines: &[String],
+    start: usize,
+    fence: Fence,
+    blocks: &mut Vec<Block>,
+) -> usize {
+    let mut end = start;
+    let mut content = Vec::new();
+    while end < lines.len() && !fence.closes(&lines[end]) {
+        let line = lines[end].as_str();
+        let strip = split_indent(line).0.min(fence.indent);
+        content.push(&line[strip..]);
+        end += 1;
+    }
+    blocks.push(Block::CodeBlock {
+        language: fence.language,
+        content: content.join("\n"),
+        meta: fence.meta,
+    });
+    // Skip the closing fence when present
+    (end + 1).min(lines.len())
+}
+
+fn atx_heading(rest: &str) -> Option<Block> {
+    let level = rest.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let after = &rest[level..];
+    if !after.is_empty() && !after.starts_with(' ') {
+        return None;
+    }
+    let mut text = after.trim();
+    // Strip an optional closing sequence of `#` characters
+    let stripped = text.trim_end_matches('#');
+    if stripped.is_empty() || stripped.ends_with(' ') {
+        text = stripped.trim_end();
+    }
+
+    // An explicit id, written as a leading anchor or a trailing attribute
+    let mut id = None;
+    if let Some(anchor) = text.strip_prefix("<a id=\"")
+        && let Some((value, rest)) = anchor.split_once("\"></a>")
+    {
+        id = Some(value.replace("&quot;", "\"").replace("&amp;", "&"));
+        text = rest;
+    } else if let Some(open) = text.rfind(" {#")
+        && let Some(value) = text[open + 3..].strip_suffix('}')
+        && !value.is_empty()
+        && !value.contains(|c: char| c.is_whitespace() || c == '}')
+    {
+        id = Some(value.to_string());
+        text = text[..open].trim_end();
+    }
+    Some(Block::Heading {
+        level: level as u8,
+        content: parse_inlines(text),
+        id,
+    })
+}
+
+fn is_thematic_break(rest: &str) -> bool {
+    let Some(marker) = rest.chars().next().filter(|c| matches!(c, '-' | '*' | '_')) else {
+        return false;
+    };
+    let mut count = 0;
+    for c in rest.chars() {
+        if c == marker {
+            count += 1;
+        } else if c != ' ' {
+            return false;
+        }
+    }
+    count >= 3
+}
+
+fn setext_level(line: &str) -> Option<u8> {
+    let (indent, rest) = split_indent(line);
+    let rest = rest.trim_end();
+    if indent >= 4 || rest.is_empty() {
+        return None;
+    }
+    if rest.chars().all(|c| c == '=') {
+        Some(1)
+    } else if rest.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+fn parse_blockquote(
+    lines: &[String],
+    start: usize,
+    depth: usize,
+    blocks: &mut Vec<Block>,
+) -> usize {
+    let mut end = start;
+    let mut inner = Vec::new();
+    while end < lines.len() {
+        let line = lines[end].as_str();
+        let (indent, rest) = split_indent(line);
+        if indent < 4 && rest.starts_with('>') {
+            let rest = &rest[1..];
+            inner.push(rest.strip_prefix(' ').unwrap_or(rest).to_string());
+        } else if !is_blank(line)
+            && inner
+                .last()
+                .is_some_and(|prev: &String| is_paragraph_line(prev))
+            && !interrupts_paragraph(line)
+        {
+            // Lazy continuation of a quoted paragraph
+            inner.push(line.to_string());
+        } else {
+            break;
+        }
+        end += 1;
+    }
+    match inner.first().and_then(|line| alert_marker(line)) {
+        Some((kind, title)) => blocks.push(Block::Callout {
+            kind,
+            title,
+            content: parse_blocks(&inner[1..], depth + 1),
+        }),
+        None => blocks.push(Block::Blockquote(parse_blocks(&inner, depth + 1))),
+    }
+    end
+}
+
+/// The kind and any title of a GitHub alert marker such as `[!WARNING] Heads up`.
+fn alert_marker(line: &str) -> Option<(CalloutKind, Option<String>)> {
+    let (name, rest) = line.trim().strip_prefix("[!")?.split_once(']')?;
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let kind = match name.to_ascii_lowercase().as_str() {
+        "caution" => CalloutKind::Danger,
+        name => CalloutKind::ALL.into_iter().find(|k| k.name() == name)?,
+    };
+    let title = rest.trim();
+    Some((
+        kind,
+        (!title.is_empty()).then(|| plain_text(&parse_inlines(title))),
+    ))
+}
+
+/// A list item marker and the column its content starts at.
+#[derive(Clone, Copy)]
+struct ListItem {
+    bullet: Option<char>,
+    delimiter: char,
+    number: usize,
+    content_indent: usize,
+    empty: bool,
+}
+
+impl ListItem {
+    fn open(line: &str) -> Option<Self> {
+        let (indent, rest) = split_indent(line);
+        if indent >= 4 {
+            return None;
+        }
+
+        let (bullet, delimiter, number, marker_len) = match rest.chars().next()? {
+            c @ ('-' | '*' | '+') => (Some(c), c, 0, 1),
+            c if c.is_ascii_digit() => {
+                let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+                let delimiter = rest[digits..].chars().next()?;
+                if digits > 9 || !matches!(delimiter, '.' | ')') {
+                    return None;
+                }
+                (None, delimiter, rest[..digits].parse().ok()?, digits + 1)
+            }
+            _ => return None,
+        };
+
+        let after = &rest[marker_len..];
+        if after.trim().is_empty() {
+            return Some(Self {
+                bullet,
+                delimiter,
+                number,
+                content_indent: indent + marker_len + 1,
+                empty: true,
+            });
+        }
+        let spaces = after.chars().take_while(|c| *c == ' ').count();
+        if spaces == 0 {
+            return None;
+        }
+        // Five or more spaces start indented code inside the item
+        let spaces = if spaces > 4 { 1 } else { spaces };
+        Some(Self {
+            bullet,
+            delimiter,
+            number,
+            content_indent: indent + marker_len + spaces,
+            empty: false,
+        })
+    }
+
+    fn same_list(&self, other: &Self) -> bool {
+        self.bullet == other.bullet && self.delimiter == other.delimiter
+    }
+}
+
+fn parse_list(
+    lines: &[String],
+    start: usize,
+    first: ListItem,
+    depth: usize,
+    blocks: &mut Vec<Block>,
+) -> usize {
+    let mut items: Vec<Vec<String>> = Vec::new();
+    let mut end = start;
+    let mut marker = first;
+
+    loop {
+        let line = lines[end].as_str();
+        let mut item = vec![line.get(marker.content_indent..).unwrap_or("").to_string()];
+        end += 1;
+
+        while end < lines.len() {
+            let line = lines[end].as_str();
+            if is_blank(line) {
+                // An empty item cannot continue past a blank line
+                if marker.empty && item.iter().all(|l| is_blank(l)) {
+                    break;
+                }
+                item.push(String::new());
+            } else if split_indent(line).0 >= marker.content_indent {
+                item.push(line[marker.content_indent..].to_string());
+            } else if ListItem::open(line).is_some() {
+                break;
+            } else if item.last().is_some_and(|prev| is_paragraph_line(prev))
+                && !interrupts_paragraph(line)
+            {
+                // Lazy continuation of the item's paragraph
+                item.push(line.trim_start().to_string());
+            } else {
+                break;
+            }
+            end += 1;
+        }
+
+        while item.last().is_some_and(|l| is_blank(l)) {
+            item.pop();
+        }
+        items.push(item);
+
+        match lines.get(end).and_then(|line| ListItem::open(line)) {
+            Some(next) if next.same_list(&marker) && !is_thematic_break(lines[end].trim()) => {
+                marker = next;
+            }
+            _ => break,
+        }
+    }
+
+    // Blank lines consumed by the final item belong between blocks
+    while end > start && is_blank(&lines[end - 1]) {
+        end -= 1;
+    }
+
+    let tasks: Option<Vec<(bool, Vec<String>)>> = match first.bullet {
+        Some(_) => items.iter().map(|item| task_marker(item)).collect(),
+        None => None,
+    };
+
+    match tasks {
+        Some(tasks) => {
+            let items = tasks
+                .into_iter()
+                .map(|(checked, lines)| (checked, item_block(parse_blocks(&lines, depth + 1))))
+                .collect();
+            blocks.push(Block::TaskList { items });
+        }
+        None => {
+            let items = items
+                .iter()
+                .map(|item| item_block(parse_blocks(item, depth + 1)))
+                .collect();
+            blocks.push(Block::List {
+                ordered: first.bullet.is_none(),
+                items,
+            });
+        }
+    }
+    end
+}
+
+/// Split a GFM task checkbox from the first line of a list item.
+fn task_marker(item: &[String]) -> Option<(bool, Vec<String>)> {
+    let first = item.first()?;
+    let checked = match first.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let rest = &first[3..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let mut lines = item.to_vec();
+    lines[0] = rest.trim_start().to_string();
+    Some((checked, lines))
+}
+
+fn item_block(mut blocks: Vec<Block>) -> Block {
+    if blocks.len() == 1 {
+        blocks.remove(0)
+    } else {
+        Block::BlockList(blocks)
+    }
+}
+
+fn is_table_start(lines: &[String], start: usize) -> bool {
+    let Some(delimiter) = lines.get(start + 1) else {
+        return false;
+    };
+    let header = lines[start].as_str();
+    header.contains('|')
+        && parse_alignments(delimiter).is_some_and(|aligns| aligns.len() == split_row(header).len())
+}
+
+fn parse_alignments(line: &str) -> Option<Vec<Alignment>> {
+    if !line.contains('-') || split_indent(line).0 >= 4 {
+        return None;
+    }
+    split_row(line)
+        .iter()
+        .map(|cell| {
+            let left = cell.starts_with(':');
+            let right = cell.ends_with(':');
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (left, right) {
+                (true, true) => Alignment::Center,
+                (false, true) => Alignment::Right,
+                _ => Alignment::Left,
+            })
+        })
+        .collect()
+}
+
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => line,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells.into_iter().map(|c| c.trim().to_string()).collect()
+}
+
+fn parse_cell(source: &str) -> Cell {
+    Cell {
+        content: parse_inlines(source),
+        colspan: 1,
+        rowspan: 1,
+    }
+}
+
+fn parse_table(lines: &[String], start: usize, blocks: &mut Vec<Block>) -> usize {
+    let headers: Vec<Cell> = split_row(&lines[start])
+        .iter()
+        .map(|cell| parse_cell(cell))
+        .collect();
+    let alignments = parse_alignments(&lines[start + 1]).unwrap_or_default();
+
+    let mut end = start + 2;
+    let mut rows = Vec::new();
+    while end < lines.len() {
+        let line = lines[end].as_str();
+        if is_blank(line) || interrupts_paragraph(line) {
+            break;
+        }
+        let mut row: Vec<Cell> = split_row(line)
+            .iter()
+            .take(headers.len())
+            .map(|cell| parse_cell(cell))
+            .collect();
+        row.resize(headers.len(), parse_cell(""));
+        rows.push(row);
+        end += 1;
+    }
+
+    // A Pandoc `Table: caption` paragraph after the table
+    let next = (end..lines.len()).find(|&i| !is_blank(&lines[i]));
+    let caption = next.and_then(|i| {
+        let (indent, rest) = split_indent(&lines[i]);
+        let caption = rest.strip_prefix("Table:").filter(|_| indent < 4)?;
+        lines
+            .get(i + 1)
+            .is_none_or(|line| is_blank(line))
+            .then(|| unescape(caption.trim(), true))
+    });
+    if caption.is_some() {
+        end = next.map_or(end, |i| i + 1);
+    }
+
+    blocks.push(Block::Table {
+        headers,
+        rows,
+        alignments,
+        footer: Vec::new(),
+        caption,
+    });
+    end
+}
+
+/// Whether a line is a `<details>` opening tag, and if so whether it is
+/// `<details open>`.
+fn details_open(line: &str) -> Option<bool> {
+    match line.trim() {
+        "<details>" => Some(false),
+        "<details open>" => Some(true),
+        _ => None,
+    }
+}
+
+fn parse_details(
+    lines: &[String],
+    start: usize,
+    open: bool,
+    depth: usize,
+    blocks: &mut Vec<Block>,
+) -> usize {
+    let mut end = start + 1;
+    let summary = lines
+        .get(end)
+        .and_then(|line| line.trim().strip_prefix("<summary>"))
+        .and_then(|line| line.strip_suffix("</summary>"));
+    if summary.is_some() {
+        end += 1;
+    }
+
+    // Content runs to the matching closing tag, or the end of the input
+    let first = end;
+    let mut unclosed = 1;
+    while end < lines.len() {
+        let line = lines[end].as_str();
+        if details_open(line).is_some() {
+            unclosed += 1;
+        } else if line.trim() == "</details>" {
+            unclosed -= 1;
+            if unclosed == 0 {
+                break;
+            }
+        }
+        end += 1;
+    }
+    blocks.push(Block::Details {
+        summary: summary.map(parse_inlines).unwrap_or_default(),
+        content: parse_blocks(&lines[first..end], depth + 1),
+        open,
+    });
+    // Skip the closing tag when present
+    (end + 1).min(lines.len())
+}
+
+/// Split `[^label]: text` into the label and the text.
+fn footnote_definition(rest: &str) -> Option<(String, &str)> {
+    let (label, after) = footnote_label(rest)?;
+    let text = after.strip_prefix(':')?;
+    Some((label, text.trim_start()))
+}
+
+/// Split `[^label]` from the start of `text`, returning the label and the
+/// text after the closing bracket.
+fn footnote_label(text: &str) -> Option<(String, &str)> {
+    let body = text.strip_prefix("[^")?;
+    let close = body.find(']')?;
+    let label = &body[..close];
+    is_footnote_label(label).then(|| (unescape(label, false), &body[close + 1..]))
+}
+
+fn is_footnote_label(label: &str) -> bool {
+    !label.is_empty() && !label.contains(|c: char| c.is_whitespace() || c == '[')
+}
+
+fn parse_footnote(
+    lines: &[String],
+    start: usize,
+    label: String,
+    first: &str,
+    depth: usize,
+    blocks: &mut Vec<Block>,
+) -> usize {
+    let mut content = vec![first.to_string()];
+    let mut end = start + 1;
+    while end < lines.len() {
+        let line = lines[end].as_str();
+        if is_blank(line) {
+            content.push(String::new());
+        } else if split_indent(line).0 >= 4 {
+            content.push(line[4..].to_string());
+        } else if content.last().is_some_and(|prev| is_paragraph_line(prev))
+            && !interrupts_paragraph(line)
+            && footnote_definition(line.trim_start()).is_none()
+        {
+            // Lazy continuation of the note's paragraph
+            content.push(line.trim_start().to_string());
+        } else {
+            break;
+        }
+        end += 1;
+    }
+    blocks.push(Block::FootnoteDefinition {
+        label,
+        content: parse_blocks(&content, depth + 1),
+    });
+    end
+}
+
+/// The text after a `: ` definition marker.
+fn definition_marker(line: &str) -> Option<&str> {
+    let (indent, rest) = split_indent(line);
+    let text = rest.strip_prefix(':').filter(|_| indent < 4)?;
+    (text.is_empty() || text.starts_with(' ')).then(|| text.trim_start())
+}
+
+/// Whether the line at `start` is a term followed by a definition.
+fn is_definition_start(lines: &[String], start: usize) -> bool {
+    is_paragraph_line(&lines[start])
+        && definition_marker(&lines[start]).is_none()
+        && lines
+            .get(start + 1)
+            .is_some_and(|line| definition_marker(line).is_some())
+}
+
+fn parse_definitions(
+    lines: &[String],
+    start: usize,
+    depth: usize,
+    blocks: &mut Vec<Block>,
+) -> usize {
+    let mut items = Vec::new();
+    let mut end = start;
+    loop {
+        let term = parse_inlines(lines[end].trim());
+        end += 1;
+
+        // Several definitions of one term become consecutive blocks
+        let mut definition = Vec::new();
+        while let Some(first) = lines.get(end).and_then(|line| definition_marker(line)) {
+            let mut content = vec![first.to_string()];
+            end += 1;
+            while end < lines.len() {
+                let line = lines[end].as_str();
+                if is_blank(line) {
+                    content.push(String::new());
+                } else if split_indent(line).0 >= 4 {
+                    content.push(line[4..].to_string());
+                } else if content.last().is_some_and(|prev| is_paragraph_line(prev))
+                    && !interrupts_paragraph(line)
+                    && definition_marker(line).is_none()
+                {
+                    // Lazy continuation of the definition's paragraph
+                    content.push(line.trim_start().to_string());
+                } else {
+                    break;
+                }
+                end += 1;
+            }
+            definition.extend(parse_blocks(&content, depth + 1));
+        }
+        items.push((term, definition));
+
+        let next = (end..lines.len())
+            .find(|&i| !is_blank(&lines[i]))
+            .unwrap_or(lines.len());
+        if next == lines.len() || !is_definition_start(lines, next) {
+            break;
+        }
+        end = next;
+    }
+
+    // Blank lines consumed by the final definition belong between blocks
+    while end > start && is_blank(&lines[end - 1]) {
+        end -= 1;
+    }
+    blocks.push(Block::DefinitionList(items));
+    end
+}
+
+fn parse_paragraph(lines: &[String], start: usize, blocks: &mut Vec<Block>) -> usize {
+    let mut end = start + 1;
+    while end < lines.len() {
+        let line = lines[end].as_str();
+        if let Some(level) = setext_level(line) {
+            let text = paragraph_text(&lines[start..end]);
+            blocks.push(Block::Heading {
+                level,
+                content: parse_inlines(&text),
+                id: None,
+            });
+            return end + 1;
+        }
+        if is_blank(line) || interrupts_paragraph(line) || is_table_start(lines, end) {
+            break;
+        }
+        end += 1;
+    }
+
+    let text = paragraph_text(&lines[start..end]);
+    let mut content = parse_inlines(&text);
+    match content.as_slice() {
+        [Inline::Image { .. }] => {
+            let Some(Inline::Image { alt, url }) = content.pop() else {
+                unreachable!()
+            };
+            blocks.push(Block::Image { alt, url });
+        }
+        _ => blocks.push(Block::Paragraph(content)),
+    }
+    end
+}
+
+fn paragraph_text(lines: &[String]) -> String {
+    let lines: Vec<&str> = lines.iter().map(|l| l.trim_start()).collect();
+    lines.join("\n").trim_end().to_string()
+}
+
+/// Whether a line of a container is still open paragraph text.
+fn is_paragraph_line(line: &str) -> bool {
+    let (indent, rest) = split_indent(line);
+    !is_blank(line)
+        && indent < 4
+        && Fence::open(rest, indent).is_none()
+        && atx_heading(rest).is_none()
+        && !is_thematic_break(rest)
+}
+
+/// Whether `line` starts a block that may interrupt a paragraph.
+fn interrupts_paragraph(line: &str) -> bool {
+    let (indent, rest) = split_indent(line);
+    if indent >= 4 {
+        return false;
+    }
+    Fence::open(rest, indent).is_some()
+        || atx_heading(rest).is_some()
+        || is_thematic_break(rest)
+        || rest.starts_with('>')
+        || ListItem::open(line)
+            .is_some_and(|item| !item.empty && (item.bullet.is_some() || item.number == 1))
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn split_indent(line: &str) -> (usize, &str) {
+    let rest = line.trim_start_matches(' ');
+    (line.len() - rest.len(), rest)
+}
+
+fn expand_tabs(line: &str) -> String {
+    let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+    let mut out = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line[..indent].chars() {
+        let width = if c == '\t' { 4 - column % 4 } else { 1 };
+        out.extend(std::iter::repeat_n(' ', width));
+        column += width;
+    }
+    out.push_str(&line[indent..]);
+    out
+}
+
+// ---------------- Inline Parsing ----------------
+
+enum Token {
+    Node(Inline),
+    Delim(Delim),
+}
+
+/// A run of emphasis characters awaiting a matching partner.
+struct Delim {
+    ch: char,
+    count: usize,
+    orig: usize,
+    can_open: bool,
+    can_close: bool,
+}
+
+fn parse_inlines(source: &str) -> Vec<Inline> {
+    parse_nested_inlines(source, 0)
+}
+
+/// Parse inline content nested `depth` elements deep, such as link text.
+fn parse_nested_inlines(source: &str, depth: usize) -> Vec<Inline> {
+    if depth > MAX_DEPTH {
+        return match source.is_empty() {
+            true => Vec::new(),
+            false => vec![Inline::Text(source.to_string())],
+        };
+    }
+    let chars: Vec<char> = source.chars().collect();
+    let closers = match_brackets(&chars);
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' => match chars.get(i + 1) {
+                Some(next) if next.is_ascii_punctuation() => {
+                    text.push(*next);
+                    i += 2;
+                }
+                Some('\n') => {
+                    flush(&mut text, &mut tokens);
+                    tokens.push(Token::Node(Inline::LineBreak));
+                    i = skip_spaces(&chars, i + 2);
+                }
+                _ => {
+                    text.push('\\');
+                    i += 1;
+                }
+            },
+            '`' => {
+                let run = run_length(&chars, i, '`');
+                match find_code_close(&chars, i + run, run) {
+                    Some(close) => {
+                        flush(&mut text, &mut tokens);
+                        let code: String = chars[i + run..close].iter().collect();
+                        tokens.push(Token::Node(Inline::Code(normalize_code(&code))));
+                        i = close + run;
+                    }
+                    None => {
+                        text.extend(&chars[i..i + run]);
+                        i += run;
+                    }
+                }
+            }
+            '*' | '_' | '~' => {
+                let run = run_length(&chars, i, c);
+                if c == '~' && run > 2 {
+                    text.extend(&chars[i..i + run]);
+                    i += run;
+                    continue;
+                }
+                let before = if i == 0 { ' ' } else { chars[i - 1] };
+                let after = chars.get(i + run).copied().unwrap_or(' ');
+                let left = !after.is_whitespace()
+                    && (!is_punct(after) || before.is_whitespace() || is_punct(before));
+                let right = !before.is_whitespace()
+                    && (!is_punct(before) || after.is_whitespace() || is_punct(after));
+                let (can_open, can_close) = if c == '_' {
+                    (
+                        left && (!right || is_punct(before)),
+                        right && (!left || is_punct(after)),
+                    )
+                } else {
+                    (left, right)
+                };
+                flush(&mut text, &mut tokens);
+                tokens.push(Token::Delim(Delim {
+                    ch: c,
+                    count: run,
+                    orig: run,
+                    can_open,
+                    can_close,
+                }));
+                i += run;
+            }
+            '!' if chars.get(i + 1) == Some(&'[') => match parse_link(&chars, &closers, i + 1) {
+                Some((content, url, next)) => {
+                    flush(&mut text, &mut tokens);
+                    let alt = plain_text(&parse_nested_inlines(&content, depth + 1));
+                    tokens.push(Token::Node(Inline::Image { alt, url }));
+                    i = next;
+                }
+                None => {
+                    text.push('!');
+                    i += 1;
+                }
+            },
+            '[' => match (
+                parse_footnote_ref(&chars, i),
+                parse_link(&chars, &closers, i),
+            ) {
+                (Some((label, next)), _) => {
+                    flush(&mut text, &mut tokens);
+                    tokens.push(Token::Node(Inline::FootnoteRef(label)));
+                    i = next;
+                }
+                (None, Some((content, url, next))) => {
+                    flush(&mut text, &mut tokens);
+                    let text = parse_nested_inlines(&content, depth + 1);
+                    tokens.push(Token::Node(Inline::Link { text, url }));
+                    i = next;
+                }
+                (None, None) => {
+                    text.push('[');
+                    i += 1;
+                }
+            },
+            '<' => match parse_autolink(&chars, i) {
+                Some((url, next)) => {
+                    flush(&mut text, &mut tokens);
+                    tokens.push(Token::Node(Inline::Link {
+                        text: vec![Inline::Text(url.clone())],
+                        url,
+                    }));
+                    i = next;
+                }
+                None => {
+                    text.push('<');
+                    i += 1;
+                }
+            },
+            '&' => match parse_entity(&chars, i) {
+                Some((decoded, next)) => {
+                    text.push(decoded);
+                    i = next;
+                }
+                None => {
+                    text.push('&');
+                    i += 1;
+                }
+            },
+            '\n' => {
+                // Only spaces in the source count, not references like `&#32;`
+                let spaces = chars[..i].iter().rev().take_while(|c| **c == ' ').count();
+                let hard = spaces >= 2;
+                text.truncate(text.len().saturating_sub(spaces));
+                if hard {
+                    flush(&mut text, &mut tokens);
+                    tokens.push(Token::Node(Inline::LineBreak));
+                } else {
+                    text.push('\n');
+                }
+                i = skip_spaces(&chars, i + 1);
+            }
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+    flush(&mut text, &mut tokens);
+    process_emphasis(tokens, depth)
+}
+
+fn flush(text: &mut String, tokens: &mut Vec<Token>) {
+    if !text.is_empty() {
+        tokens.push(Token::Node(Inline::Text(std::mem::take(text))));
+    }
+}
+
+fn is_punct(c: char) -> bool {
+    c.is_ascii_punctuation() || (!c.is_ascii() && !c.is_alphanumeric() && !c.is_whitespace())
+}
+
+fn run_length(chars: &[char], start: usize, c: char) -> usize {
+    chars[start..].iter().take_while(|x| **x == c).count()
+}
+
+fn skip_spaces(chars: &[char], mut i: usize) -> usize {
+    while chars.get(i) == Some(&' ') {
+        i += 1;
+    }
+    i
+}
+
+fn find_code_close(chars: &[char], mut i: usize, run: usize) -> Option<usize> {
+    while i < chars.len() {
+        if chars[i] == '`' {
+            let len = run_length(chars, i, '`');
+            if len == run {
+                return Some(i);
+            }
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+fn normalize_code(code: &str) -> String {
+    let code = code.replace('\n', " ");
+    let stripped = code
+        .strip_prefix(' ')
+        .and_then(|c| c.strip_suffix(' '))
+        .filter(|_| !code.chars().all(|c| c == ' '));
+    stripped.unwrap_or(&code).to_string()
+}
+
+/// Find the bracket closing each opening bracket, skipping escapes and code
+/// spans, in a single pass so that unmatched brackets cost nothing later.
+fn match_brackets(chars: &[char]) -> Vec<Option<usize>> {
+    let mut closers = vec![None; chars.len()];
+    let mut open = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '`' => {
+                let run = run_length(chars, i, '`');
+                i = find_code_close(chars, i + run, run).map_or(i + run - 1, |c| c + run - 1);
+            }
+            '[' => open.push(i),
+            ']' => {
+                if let Some(opener) = open.pop() {
+                    closers[opener] = Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    closers
+}
+
+/// Parse `[text](destination "title")` starting at the opening bracket,
+/// given the closing brackets found by [`match_brackets`].
+fn parse_link(
+    chars: &[char],
+    closers: &[Option<usize>],
+    open: usize,
+) -> Option<(String, String, usize)> {
+    let close = closers[open]?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+
+    let mut i = skip_whitespace(chars, close + 2);
+    let mut url = String::new();
+    if chars.get(i) == Some(&'<') {
+        i += 1;
+        loop {
+            match chars.get(i)? {
+                '>' => break,
+                '\n' | '<' => return None,
+                '\\' if chars.get(i + 1).is_some_and(|c| c.is_ascii_punctuation()) => {
+                    url.push(chars[i + 1]);
+                    i += 1;
+                }
+                c => url.push(*c),
+            }
+            i += 1;
+        }
+        i += 1;
+    } else {
+        let mut parens = 0;
+        while let Some(&c) = chars.get(i) {
+            match c {
+                c if c.is_whitespace() || c.is_control() => break,
+                '(' => parens += 1,
+                ')' if parens == 0 => break,
+                ')' => parens -= 1,
+                '\\' if chars.get(i + 1).is_some_and(|c| c.is_ascii_punctuation()) => {
+                    i += 1;
+                    url.push(chars[i]);
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            url.push(c);
+            i += 1;
+        }
+    }
+
+    let mut i = skip_whitespace(chars, i);
+    if let Some(&quote) = chars.get(i).filter(|c| matches!(c, '"' | '\'' | '(')) {
+        let end = if quote == '(' { ')' } else { quote };
+        i += 1;
+        while *chars.get(i)? != end {
+            // Titles stop at the next link, which keeps unclosed ones cheap
+            if chars[i] == '[' {
+                return None;
+            }
+            if chars[i] == '\\' {
+                i += 1;
+            }
+            i += 1;
+        }
+        i = skip_whitespace(chars, i + 1);
+    }
+    if chars.get(i) != Some(&')') {
+        return None;
+    }
+
+    let content = chars[open + 1..close].iter().collect();
+    Some((content, unescape(&url, true), i + 1))
+}
+
+/// Parse a `[^label]` footnote reference starting at the opening bracket,
+/// unless it is the text of a link.
+fn parse_footnote_ref(chars: &[char], open: usize) -> Option<(String, usize)> {
+    if chars.get(open + 1) != Some(&'^') {
+        return None;
+    }
+    // Labels hold no whitespace or brackets, so the scan stops at either
+    let close = open
+        + 2
+        + chars[open + 2..]
+            .iter()
+            .take_while(|c| !matches!(c, '[' | ']') && !c.is_whitespace())
+            .count();
+    if chars.get(close) != Some(&']') {
+        return None;
+    }
+    let label: String = chars[open + 2..close].iter().collect();
+    if !is_footnote_label(&label) || chars.get(close + 1) == Some(&'(') {
+        return None;
+    }
+    Some((unescape(&label, false), close + 1))
+}
+
+fn skip_whitespace(chars: &[char], mut i: usize) -> usize {
+    while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+        i += 1;
+    }
+    i
+}
+
+fn parse_autolink(chars: &[char], open: usize) -> Option<(String, usize)> {
+    // Autolinks hold no whitespace or `<`, so the scan stops at either
+    let close = open
+        + 1
+        + chars[open + 1..]
+            .iter()
+            .take_while(|c| !matches!(c, '<' | '>') && !c.is_whitespace())
+            .count();
+    if chars.get(close) != Some(&'>') {
+        return None;
+    }
+    let url: String = chars[open + 1..close].iter().collect();
+    let scheme = url.split(':').next()?;
+    let valid_scheme = (2..=32).contains(&scheme.len())
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'));
+    if !valid_scheme || url.len() == scheme.len() {
+        return None;
+    }
+    Some((url, close + 1))
+}
+
+fn parse_entity(chars: &[char], amp: usize) -> Option<(char, usize)> {
+    let semi = amp + chars[amp..].iter().take(34).position(|c| *c == ';')?;
+    let name: String = chars[amp + 1..semi].iter().collect();
+    let decoded = match name.as_str() {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)
+                .filter(|c| *c != '\0')
+                .unwrap_or('\u{fffd}')
+        }
+    };
+    Some((decoded, semi + 1))
+}
+
+/// Resolve backslash escapes and, optionally, entity references.
+fn unescape(value: &str, entities: bool) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if chars.get(i + 1).is_some_and(|c| c.is_ascii_punctuation()) => {
+                out.push(chars[i + 1]);
+                i += 2;
+            }
+            '&' if entities => match parse_entity(&chars, i) {
+                Some((decoded, next)) => {
+                    out.push(decoded);
+                    i = next;
+                }
+                None => {
+                    out.push('&');
+                    i += 1;
+                }
+            },
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Match emphasis delimiters following the CommonMark delimiter algorithm.
+fn process_emphasis(mut tokens: Vec<Token>, depth: usize) -> Vec<Inline> {
+    // Lowest position that may still hold an opener for each kind of
+    // closer, so closers without openers don't rescan the same tokens
+    let mut bottoms: HashMap<(char, bool, usize), usize> = HashMap::new();
+    let mut closer = 0;
+    while closer < tokens.len() {
+        let Token::Delim(close) = &tokens[closer] else {
+            closer += 1;
+            continue;
+        };
+        if !close.can_close {
+            closer += 1;
+            continue;
+        }
+
+        let kind = match close.ch {
+            '~' => (close.ch, close.can_open, close.count),
+            _ => (close.ch, close.can_open, close.orig % 3),
+        };
+        let bottom = bottoms.get(&kind).copied().unwrap_or(0);
+        let opener = (bottom..closer).rev().find(|&o| match &tokens[o] {
+            Token::Delim(open) if open.ch == close.ch && open.can_open => {
+                if close.ch == '~' {
+                    open.count == close.count
+                } else {
+                    let both = open.can_close || close.can_open;
+                    !(both
+                        && (open.orig + close.orig) % 3 == 0
+                        && !(open.orig % 3 == 0 && close.orig % 3 == 0))
+                }
+            }
+            _ => false,
+        });
+        let Some(opener) = opener else {
+            bottoms.insert(kind, closer);
+            closer += 1;
+            continue;
+        };
+
+        // Emphasis nested too deeply stays literal
+        let limit = MAX_DEPTH.saturating_sub(depth);
+        let nested = tokens[opener + 1..closer]
+            .iter()
+            .map(|token| match token {
+                Token::Node(node) => nesting(node, limit),
+                Token::Delim(_) => 0,
+            })
+            .max()
+            .unwrap_or(0);
+        if nested >= limit {
+            closer += 1;
+            continue;
+        }
+
+        let (Token::Delim(open), Token::Delim(close)) = (&tokens[opener], &tokens[closer]) else {
+            unreachable!()
+        };
+        let used = match close.ch {
+            '~' => close.count,
+            _ if open.count >= 2 && close.count >= 2 => 2,
+            _ => 1,
+        };
+        let ch = close.ch;
+
+        for bottom in bottoms.values_mut() {
+            *bottom = (*bottom).min(opener);
+        }
+        let children = finish(tokens.drain(opener + 1..closer).collect());
+        let node = match (ch, used) {
+            ('~', _) => Inline::Strikethrough(children),
+            (_, 2) => Inline::Bold(children),
+            _ => Inline::Italic(children),
+        };
+        tokens.insert(opener + 1, Token::Node(node));
+
+        closer = opener + 2;
+        if let Token::Delim(close) = &mut tokens[closer] {
+            close.count -= used;
+            if close.count == 0 {
+                tokens.remove(closer);
+            }
+        }
+        if let Token::Delim(open) = &mut tokens[opener] {
+            open.count -= used;
+            if open.count == 0 {
+                tokens.remove(opener);
+                closer -= 1;
+            }
+        }
+    }
+    finish(tokens)
+}
+
+/// How many inline elements are nested in `inline`, counting at most
+/// `limit` levels.
+fn nesting(inline: &Inline, limit: usize) -> usize {
+    let children = match inline {
+        Inline::Bold(children)
+        | Inline::Italic(children)
+        | Inline::Strikethrough(children)
+        | Inline::Link { text: children, .. } => children,
+        _ => return 0,
+    };
+    match limit {
+        0 | 1 => 1,
+        _ => {
+            let inner = children.iter().map(|child| nesting(child, limit - 1));
+            1 + inner.max().unwrap_or(0)
+        }
+    }
+}
+
+/// Turn leftover delimiters into text and merge adjacent text nodes.
+fn finish(tokens: Vec<Token>) -> Vec<Inline> {
+    let mut out: Vec<Inline> = Vec::new();
+    for token in tokens {
+        let node = match token {
+            Token::Node(node) => node,
+            Token::Delim(delim) => Inline::Text(delim.ch.to_string().repeat(delim.count)),
+        };
+        match (out.last_mut(), node) {
+            (Some(Inline::Text(prev)), Inline::Text(next)) => prev.push_str(&next),
+            (_, node) => out.push(node),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::*;
+    use crate::md::Renderer;
+
+    fn round_trip(blocks: Vec<Block>) {
+        let markdown = Renderer::to_string(&blocks[..]);
+        assert_eq!(parse(&markdown), blocks, "markdown:\n{markdown}");
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let blocks = parse(
+            "Title\n=====\n\n## Sub ##\n\n### Id {#sub-id}\n\n```rust\nfn main() {}\n```\n\n    indented\n\n***\n\n> quoted\nlazy\n",
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                h1("Title"),
+                h2("Sub"),
+                h3("Id").with_id("sub-id"),
+                code_block("rust", "fn main() {}"),
+                code_block((), "indented"),
+                hr(),
+                quote(p("quoted\nlazy")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_code_info() {
+        let blocks = parse("``` title=\"a b.rs\" {1, 3-4} extra\nx\n```\n\n~~~sh {2}\ny\n~~~\n");
+        assert_eq!(
+            blocks,
+            vec![
+                code_block((), "x")
+                    .with_title("a b.rs")
+                    .with_highlighted([1, 3, 4]),
+                code_block("sh", "y").with_highlighted([2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inlines() {
+        let blocks = parse("*a **b** c* ~~gone~~ `x` [go](<u r l> \"t\") \\*lit\\* <https://a.b>");
+        assert_eq!(
+            blocks,
+            vec![p((
+                italic(("a ", bold("b"), " c")),
+                " ",
+                strikethrough("gone"),
+                " ",
+                code("x"),
+                " ",
+                link("go", "u r l"),
+                " *lit* ",
+                link("https://a.b", "https://a.b"),
+            ))]
+        );
+
+        assert_eq!(
+            parse("***both***"),
+            vec![p(vec![italic(vec![bold("both")])])]
+        );
+        assert_eq!(parse("snake_case_name"), vec![p("snake_case_name")]);
+        assert_eq!(
+            parse("hard  \nbreak"),
+            vec![p(("hard", Inline::LineBreak, "break"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_lists() {
+        let blocks = parse("- [x] done\n- [ ] todo\n\n1. one\n2. two\n   - nested\n");
+        assert_eq!(
+            blocks,
+            vec![
+                task_list([(true, "done"), (false, "todo")]),
+                ol(["one".p(), block((p("two"), ul(["nested"])))]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_footnotes() {
+        let blocks = parse(
+            "Text[^1] and [^note](x) [^a b].\n\n[^1]: First\nlazy\n\n    More.\n\n[^x]: Next\n",
+        );
+        let note = |label: &str, content| Block::FootnoteDefinition {
+            label: label.into(),
+            content,
+        };
+        assert_eq!(
+            blocks,
+            vec![
+                p((
+                    "Text",
+                    Inline::FootnoteRef("1".into()),
+                    " and ",
+                    link("^note", "x"),
+                    " [^a b]."
+                )),
+                note("1", vec![p("First\nlazy"), p("More.")]),
+                note("x", vec![p("Next")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_callouts() {
+        let blocks = parse(
+            "> [!tip]\n> Use `--release`.\n\n> [!OOPS]\n> quoted\n\n> [!NOTE] text\n\n> [!NOTE]\n> **Heads up**\n",
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                tip(p(("Use ", code("--release"), "."))),
+                quote(p("[!OOPS]\nquoted")),
+                note(Vec::<Block>::new()).with_title("text"),
+                note(p(bold("Heads up"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_definition_lists() {
+        let blocks = parse(
+            "`-v`\n: Verbose\nlazy\n\n    More.\n: Again\n\nTerm\n:   Def\n\nPlain\n:not a marker\n",
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                dl((
+                    (
+                        code("-v"),
+                        block((p("Verbose\nlazy"), p("More."), p("Again")))
+                    ),
+                    ("Term", p("Def")),
+                )),
+                p("Plain\n:not a marker"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_details() {
+        let blocks = parse(
+            "<details open>\n<summary>Log &amp; <b>output</b></summary>\n\n\
+             - a\n\n<details>\n\ninner\n\n</details>\n</details>\n\n<details>\nunclosed\n",
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                details(
+                    "Log & <b>output</b>",
+                    (ul(["a"]), details(Vec::<Inline>::new(), p("inner")))
+                )
+                .with_open(true),
+                details(Vec::<Inline>::new(), p("unclosed")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let blocks = parse(
+            "| Name | Age |\n| :-: | --: |\n| **Al** Smith | 30 |\n| Bo |\n\nTable: People\n",
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                table(
+                    (Align::center("Name"), Align::right("Age")),
+                    ((cell((bold("Al"), " Smith")), "30"), ("Bo", "")),
+                )
+                .with_caption("People")
+            ]
+        );
+
+        // A caption must stand alone as a paragraph
+        let blocks = parse("| A |\n| - |\n\nTable: not\na caption\n");
+        assert!(matches!(blocks[0], Block::Table { caption: None, .. }));
+        assert_eq!(blocks.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_hostile_input() {
+        // Nesting stops at a fixed depth instead of overflowing the stack
+        let blocks = parse(&format!("{}x", "> ".repeat(20_000)));
+        let mut depth = 0;
+        let mut block = &blocks[0];
+        while let Block::Blockquote(inner) = block {
+            depth += 1;
+            block = &inner[0];
+        }
+        assert_eq!(depth, MAX_DEPTH + 1);
+        assert!(
+            matches!(block, Block::Paragraph(text) if text[0] == Inline::Text(
+                format!("{}x", "> ".repeat(19_999 - MAX_DEPTH)).trim_end().into()
+            ))
+        );
+        fn inline_depth(inlines: &[Inline]) -> usize {
+            let depth = |inline: &Inline| match inline {
+                Inline::Bold(inner)
+                | Inline::Italic(inner)
+                | Inline::Strikethrough(inner)
+                | Inline::Link { text: inner, .. } => 1 + inline_depth(inner),
+                _ => 0,
+            };
+            inlines.iter().map(depth).max().unwrap_or(0)
+        }
+        let content = |blocks: &[Block]| match blocks {
+            [Block::Paragraph(content)] => content.clone(),
+            blocks => panic!("expected one paragraph, got {blocks:?}"),
+        };
+        let links = format!("{}x{}", "[".repeat(20_000), "](u)".repeat(20_000));
+        let inlines = content(&parse(&links));
+        assert_eq!(inline_depth(&inlines), MAX_DEPTH + 1);
+        let emphasis = format!("{}x{}", "*".repeat(20_000), "*".repeat(20_000));
+        let inlines = content(&parse(&emphasis));
+        assert_eq!(inline_depth(&inlines), MAX_DEPTH);
+
+        // Unmatched openers and closers stay literal text
+        for unmatched in ["[", "![", "[^", "<", "[a](u '", "a*."] {
+            let source = unmatched.repeat(80_000 / unmatched.len());
+            assert_eq!(parse(&source), [p(source.as_str())]);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(vec![
+            h1("Docloom Overview"),
+            p((bold("Docloom"), " turns structured blocks into Markdown.")),
+            h2("Getting Started").with_id("start \"here\" & now"),
+            p(("Compose ", italic("inline styles"), " and render them.")),
+            code_block("rust", "fn main() {\n    println!(\"hello\");\n}"),
+            code_block((), "plain"),
+            code_block("rust", "let a = 1;\nlet b = a / 0;")
+                .with_title("src/\"main\".rs")
+                .with_start_line(40)
+                .with_highlighted([41, 43, 44]),
+            code_block((), "numbered").with_start_line(1),
+            ul(["Supports bullet lists", "And numbered ones"]),
+            ol([p(("Call ", code("doc"))), p("Render the output")]),
+            task_list([(true, p("Choose")), (false, p("Render"))]),
+            table(
+                (
+                    Align::left("Feature"),
+                    Align::center("Kind"),
+                    Align::right("Count"),
+                ),
+                (
+                    ("Tables", "block", "1"),
+                    (
+                        "Links",
+                        cell(("See ", link("docs", "https://docs.rs"))),
+                        "2",
+                    ),
+                ),
+            )
+            .with_caption("Features: *all*"),
+            ul([
+                Block::BlockList(vec![p("Nested"), ol(["inner", "items"])]),
+                Block::BlockList(vec![p("Loose"), p("item")]),
+            ]),
+            quote((p("First."), p(vec![strikethrough("Second.")]))),
+            note(p("Plain note.")),
+            tip((p("Nested"), ul(["items"]))).with_title("Pro tip"),
+            danger(p("Gone.")),
+            Block::Image {
+                alt: "logo".into(),
+                url: "logo.png".into(),
+            },
+            p((
+                "See ",
+                Inline::Image {
+                    alt: "icon".into(),
+                    url: "icon.png".into(),
+                },
+            )),
+            hr(),
+            task_list([(
+                false,
+                Block::BlockList(vec![p("Parent"), task_list([(true, p("Child"))])]),
+            )]),
+            p(("Cited", Inline::FootnoteRef("1".into()), ".")),
+            Block::FootnoteDefinition {
+                label: "1".into(),
+                content: vec![p("Source."), ul(["a", "b"])],
+            },
+            dl((
+                (code("--verbose"), "Print every step."),
+                (
+                    ("Key ", italic("name")),
+                    block((p("First."), ul(["a", "b"]))),
+                ),
+            )),
+            details("CI log &", code_block((), "ok")),
+            details("Expanded", (p("Body."), ul(["a"]))).with_open(true),
+            p("Generate complete documents."),
+        ]);
+    }
+
+    #[test]
+    fn test_round_trip_escaping() {
+        round_trip(vec![
+            h2("Literal *stars* and `ticks`"),
+            h2("ends with #"),
+            h3("#"),
+            p("# not a heading\n1. not a list\n> not a quote\n: not a definition"),
+            p("    four spaces"),
+            p("\ttab\n  indented line"),
+            p("a  \nb \nc"),
+            p("[brackets](x) ~tilde~ _under_ snake_case \\ <b> &amp; a|b"),
+            p((code("``"), " ", code(" padded "), " ", code("a`b"))),
+            p(vec![link(("text ", bold("*")), "https://x.y/a b?q=(1")]),
+            table(("a|b", code("c|d")), [("**", "_")]),
+            code_block("md", "```\nnested fence\n```"),
+        ]);
+    }
+}
@@ -0,0 +1,352 @@
+//! Hierarchical section numbering for headings.
+
+use crate::{Block, Inline, VisitorMut, walk_block_mut};
+
+/// Numbers headings as `1.`, `1.2`, `1.2.3`, ... in document order.
+///
+/// A heading that skips levels is numbered one step deeper than the closest
+/// preceding heading of a lower level, matching how [`crate::Toc`] nests it.
+/// Each heading level can have its own [`LevelFormat`].
+///
+/// # Examples
+/// ```rust
+/// use docloom::prelude::*;
+/// use docloom::{LevelFormat, NumberStyle, Numbering, md};
+///
+/// let markdown = md::doc([h1("Spec"), h2("Scope"), h3("Terms"), h2("Rules")])
+///     .with_numbering(Numbering::new().levels(2, 6))
+///     .to_string();
+/// assert!(markdown.contains("## 1. Scope\n\n### 1.1 Terms\n\n## 2. Rules"));
+///
+/// let numbering = Numbering::new()
+///     .level(1, LevelFormat::new(NumberStyle::UpperRoman).separator("-"))
+///     .level(2, LevelFormat::new(NumberStyle::LowerAlpha).start(2).suffix(")"));
+/// let markdown = md::doc([h1("Spec"), h2("Scope")]).with_numbering(numbering).to_string();
+/// assert!(markdown.starts_with("# I. Spec\n\n## I-b) Scope\n\n"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Numbering {
+    min_level: u8,
+    max_level: u8,
+    start: usize,
+    formats: [LevelFormat; 6],
+}
+
+impl Default for Numbering {
+    fn default() -> Self {
+        Self {
+            min_level: 1,
+            max_level: 6,
+            start: 1,
+            formats: [LevelFormat::default(); 6],
+        }
+    }
+}
+
+/// Counter styles for section numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NumberStyle {
+    /// `1`, `2`, `3`, ...
+    #[default]
+    Decimal,
+    /// `a`, `b`, ..., `z`, `aa`, ...
+    LowerAlpha,
+    /// `A`, `B`, ..., `Z`, `AA`, ...
+    UpperAlpha,
+    /// `i`, `ii`, `iii`, ..., falling back to decimal above 3999.
+    LowerRoman,
+    /// `I`, `II`, `III`, ..., falling back to decimal above 3999.
+    UpperRoman,
+}
+
+impl NumberStyle {
+    fn format(self, n: usize) -> String {
+        match self {
+            NumberStyle::LowerAlpha | NumberStyle::UpperAlpha if n > 0 => {
+                let base = if self == NumberStyle::LowerAlpha {
+                    b'a'
+                } else {
+                    b'A'
+                };
+                let mut letters = Vec::new();
+                let mut n = n;
+                while n > 0 {
+                    n -= 1;
+                    letters.push((base + (n % 26) as u8) as char);
+                    n /= 26;
+                }
+                letters.iter().rev().collect()
+            }
+            NumberStyle::LowerRoman | NumberStyle::UpperRoman if (1..4000).contains(&n) => {
+                const NUMERALS: [(usize, &str); 13] = [
+                    (1000, "M"),
+                    (900, "CM"),
+                    (500, "D"),
+                    (400, "CD"),
+                    (100, "C"),
+                    (90, "XC"),
+                    (50, "L"),
+                    (40, "XL"),
+                    (10, "X"),
+                    (9, "IX"),
+                    (5, "V"),
+                    (4, "IV"),
+                    (1, "I"),
+                ];
+                let mut roman = String::new();
+                let mut n = n;
+                for (value, numeral) in NUMERALS {
+                    while n >= value {
+                        roman.push_str(numeral);
+                        n -= value;
+                    }
+                }
+                if self == NumberStyle::LowerRoman {
+                    roman.make_ascii_lowercase();
+                }
+                roman
+            }
+            _ => n.to_string(),
+        }
+    }
+}
+
+/// How the counter of one heading level is written.
+///
+/// # Examples
+/// ```rust
+/// use docloom::{LevelFormat, NumberStyle};
+///
+/// // Appendix-style `A:` numbers
+/// let appendix = LevelFormat::new(NumberStyle::UpperAlpha).suffix(":");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LevelFormat {
+    style: NumberStyle,
+    start: Option<usize>,
+    separator: &'static str,
+    suffix: Option<&'static str>,
+}
+
+impl Default for LevelFormat {
+    fn default() -> Self {
+        Self::new(NumberStyle::Decimal)
+    }
+}
+
+impl LevelFormat {
+    /// Write counters in `style`, separated from deeper ones by `.`.
+    pub fn new(style: NumberStyle) -> Self {
+        Self {
+            style,
+            start: None,
+            separator: ".",
+            suffix: None,
+        }
+    }
+
+    /// Count the first section at this level under each parent as `start`
+    /// instead of 1, or instead of [`Numbering::start`] at the top.
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Write `separator` between this level's counter and a deeper one.
+    pub fn separator(mut self, separator: &'static str) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// End numbers of headings at this level with `suffix`. By default only
+    /// single counters get a trailing `.`, as in `1.` but `1.2`.
+    pub fn suffix(mut self, suffix: &'static str) -> Self {
+        self.suffix = Some(suffix);
+        self
+    }
+}
+
+impl Numbering {
+    /// Number every heading level, starting from section 1.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only number headings from level `min` to `max`, inclusive.
+    pub fn levels(mut self, min: u8, max: u8) -> Self {
+        self.min_level = min;
+        self.max_level = max;
+        self
+    }
+
+    /// Number the first top-level section `start` instead of 1.
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Write the counter of heading `level` with `format`. Levels outside 1
+    /// to 6 are clamped.
+    pub fn level(mut self, level: u8, format: LevelFormat) -> Self {
+        self.formats[level.clamp(1, 6) as usize - 1] = format;
+        self
+    }
+
+    fn format(&self, level: u8) -> &LevelFormat {
+        &self.formats[level.clamp(1, 6) as usize - 1]
+    }
+
+    /// Prefix each numbered heading in `blocks` with its section number.
+    pub fn apply(&self, blocks: &mut [Block]) {
+        let mut counter = Counter {
+            numbering: *self,
+            stack: Vec::new(),
+        };
+        blocks.iter_mut().for_each(|b| counter.visit_block_mut(b));
+    }
+}
+
+/// Running section counts while walking a document.
+struct Counter {
+    numbering: Numbering,
+    /// Level and count of each open section, outermost first.
+    stack: Vec<(u8, usize)>,
+}
+
+impl Counter {
+    fn next(&mut self, level: u8) -> String {
+        // Depth and count of the shallowest section this heading closes
+        let mut closed = None;
+        while self.stack.last().is_some_and(|(l, _)| *l > level) {
+            let (_, count) = self.stack.pop().unwrap();
+            closed = Some((self.stack.len(), count));
+        }
+        match self.stack.last_mut() {
+            Some((l, count)) if *l == level => *count += 1,
+            _ => {
+                // Continue after a closed sibling, even one at another level
+                let depth = self.stack.len();
+                let count = match closed {
+                    Some((d, count)) if d == depth => count + 1,
+                    _ => match self.numbering.format(level).start {
+                        Some(start) => start,
+                        None if depth == 0 => self.numbering.start,
+                        None => 1,
+                    },
+                };
+                self.stack.push((level, count));
+            }
+        }
+
+        let mut number = String::new();
+        for (i, (level, count)) in self.stack.iter().enumerate() {
+            let format = self.numbering.format(*level);
+            number.push_str(&format.style.format(*count));
+            if i + 1 < self.stack.len() {
+                number.push_str(format.separator);
+            } else {
+                let single = if self.stack.len() == 1 { "." } else { "" };
+                number.push_str(format.suffix.unwrap_or(single));
+            }
+        }
+        number
+    }
+}
+
+impl VisitorMut for Counter {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        if let Block::Heading { level, content, .. } = block {
+            let range = self.numbering.min_level..=self.numbering.max_level;
+            if range.contains(level) {
+                let number = self.next(*level);
+                content.insert(0, Inline::Text(format!("{number} ")));
+            }
+        }
+        walk_block_mut(self, block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn numbers(numbering: Numbering, mut blocks: Vec<Block>) -> Vec<String> {
+        numbering.apply(&mut blocks);
+        blocks
+            .into_iter()
+            .filter_map(|b| match b {
+                Block::Heading { content, .. } => match &content[0] {
+                    Inline::Text(text) => Some(text.trim_end().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_numbering() {
+        let blocks = vec![
+            h1("A"),
+            h2("A.1"),
+            h3("A.1.1"),
+            h3("A.1.2"),
+            h2("A.2"),
+            h1("B"),
+            h3("B.1"),
+            h2("B.2"),
+        ];
+        assert_eq!(
+            numbers(Numbering::new(), blocks),
+            ["1.", "1.1", "1.1.1", "1.1.2", "1.2", "2.", "2.1", "2.2"]
+        );
+    }
+
+    #[test]
+    fn test_numbering_levels_and_start() {
+        let mut blocks = vec![
+            h1("Title"),
+            h2("Intro"),
+            h3("Scope"),
+            h2("Rules"),
+            h4("Note"),
+        ];
+        Numbering::new().levels(2, 3).start(3).apply(&mut blocks);
+        assert_eq!(
+            crate::md::Renderer::to_string(&blocks[..]),
+            "# Title\n\n## 3. Intro\n\n### 3.1 Scope\n\n## 4. Rules\n\n#### Note\n\n"
+        );
+    }
+
+    #[test]
+    fn test_numbering_level_formats() {
+        let blocks = vec![
+            h1("A"),
+            h2("A.a"),
+            h3("A.a.1"),
+            h2("A.b"),
+            h1("B"),
+            h2("B.a"),
+        ];
+        let numbering = Numbering::new()
+            .start(4)
+            .level(1, LevelFormat::new(NumberStyle::UpperRoman).separator("-"))
+            .level(2, LevelFormat::new(NumberStyle::LowerAlpha).suffix(")"))
+            .level(3, LevelFormat::default().start(0).suffix("."));
+        assert_eq!(
+            numbers(numbering, blocks),
+            ["IV.", "IV-a)", "IV-a.0.", "IV-b)", "V.", "V-a)"]
+        );
+
+        let counters = |style: NumberStyle| [0, 1, 26, 27, 1994, 4000].map(|n| style.format(n));
+        assert_eq!(
+            counters(NumberStyle::UpperAlpha),
+            ["0", "A", "Z", "AA", "BXR", "EWV"]
+        );
+        assert_eq!(
+            counters(NumberStyle::LowerRoman),
+            ["0", "i", "xxvi", "xxvii", "mcmxciv", "4000"]
+        );
+    }
+}
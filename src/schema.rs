@@ -0,0 +1,291 @@
+//! Serde support for the document tree, enabled by the `serde` feature.
+//!
+//! Every node is an object tagged with a snake_case `type` field. Variants
+//! that wrap a single value store it under `content`, and struct variants
+//! keep their field names, so a document serializes to JSON like:
+//!
+//! ```json
+//! [
+//!   { "type": "heading", "level": 1, "content": [{ "type": "text", "content": "Report" }] },
+//!   { "type": "paragraph", "content": [
+//!     { "type": "bold", "content": [{ "type": "text", "content": "Done" }] },
+//!     { "type": "line_break" }
+//!   ] },
+//!   { "type": "code_block", "language": "rust", "content": "fn main() {}" },
+//!   { "type": "task_list", "items": [
+//!     { "checked": true, "content": { "type": "paragraph", "content": [] } }
+//!   ] },
+//!   { "type": "table", "headers": [], "rows": [], "alignments": ["left", "center", "right"] },
+//!   { "type": "horizontal_rule" }
+//! ]
+//! ```
+//!
+//! Block types are:
+//!
+//! - `paragraph`, `blockquote`, `block_list`, `image` and `horizontal_rule`.
+//! - `heading`, whose `level` is clamped to 1 to 6 and whose `id` is only
+//!   written when set.
+//! - `code_block`, where an omitted or `null` `language` means no language
+//!   tag; its `title`, `start_line` and `highlighted` line numbers are only
+//!   written when set.
+//! - `list` and `task_list`.
+//! - `table`, whose cells are `{ "content": [...] }` objects with optional
+//!   `colspan` and `rowspan` counts, where 0 reads as 1; its `footer` rows
+//!   and `caption` are only written when set.
+//! - `footnote_definition`.
+//! - `callout`, whose `kind` is `note`, `tip`, `important`, `warning` or
+//!   `danger`.
+//! - `definition_list`, whose `content` holds
+//!   `{ "term": [...], "content": [...] }` items.
+//! - `details`, which is closed when `open` is omitted.
+//!
+//! Inline types are `text`, `bold`, `italic`, `strikethrough`, `code`,
+//! `link`, `image`, `line_break` and `footnote_ref`.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Block;
+
+/// Store a wrapped value under a `content` key next to the variant tag.
+pub(crate) mod content {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Content<T> {
+        content: T,
+    }
+
+    pub(crate) fn serialize<T: Serialize, S: Serializer>(
+        content: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Content { content }.serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        Content::deserialize(deserializer).map(|c| c.content)
+    }
+}
+
+/// Clamp heading levels to 1 to 6, as the builders do.
+pub(crate) fn heading_level<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    u8::deserialize(deserializer).map(|level| level.clamp(1, 6))
+}
+
+/// Read table cell spans of 0 as 1, as the builders do.
+pub(crate) fn span<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    usize::deserialize(deserializer).map(|span| span.max(1))
+}
+
+/// Default for table cell spans.
+pub(crate) fn one() -> usize {
+    1
+}
+
+/// Spans of 1 are the default and left out.
+pub(crate) fn is_one(span: &usize) -> bool {
+    *span == 1
+}
+
+/// Represent task list items as `{ "checked": bool, "content": Block }`.
+pub(crate) mod tasks {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct TaskRef<'a> {
+        checked: bool,
+        content: &'a Block,
+    }
+
+    #[derive(Deserialize)]
+    struct Task {
+        checked: bool,
+        content: Block,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        items: &[(bool, Block)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(items.iter().map(|(checked, content)| TaskRef {
+            checked: *checked,
+            content,
+        }))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(bool, Block)>, D::Error> {
+        let tasks = Vec::<Task>::deserialize(deserializer)?;
+        Ok(tasks
+            .into_iter()
+            .map(|task| (task.checked, task.content))
+            .collect())
+    }
+}
+
+/// Represent definition list items as
+/// `{ "term": [Inline], "content": [Block] }` under a `content` key.
+pub(crate) mod definitions {
+    use super::*;
+    use crate::Inline;
+
+    type Items = Vec<(Vec<Inline>, Vec<Block>)>;
+
+    #[derive(Serialize)]
+    struct DefinitionRef<'a> {
+        term: &'a [Inline],
+        content: &'a [Block],
+    }
+
+    #[derive(Deserialize)]
+    struct Definition {
+        term: Vec<Inline>,
+        content: Vec<Block>,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        items: &[(Vec<Inline>, Vec<Block>)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let items: Vec<_> = items
+            .iter()
+            .map(|(term, content)| DefinitionRef { term, content })
+            .collect();
+        super::content::serialize(&items, serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Items, D::Error> {
+        let items: Vec<Definition> = super::content::deserialize(deserializer)?;
+        Ok(items
+            .into_iter()
+            .map(|item| (item.term, item.content))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::{Block, Cell, Inline};
+    use serde_json::json;
+
+    #[test]
+    fn test_schema() {
+        let doc = vec![
+            h1("Report"),
+            p((
+                bold("Done"),
+                Inline::LineBreak,
+                link("docs", "https://docs.rs"),
+            )),
+            code_block((), "plain"),
+            task_list([(true, p("Ship"))]),
+            table((Align::left("A"), Align::right("B")), [("1", "2")]),
+            dl([("Term", "Meaning")]),
+            hr(),
+        ];
+        let text = |s: &str| json!({ "type": "text", "content": s });
+        assert_eq!(
+            serde_json::to_value(&doc).unwrap(),
+            json!([
+                { "type": "heading", "level": 1, "content": [text("Report")] },
+                { "type": "paragraph", "content": [
+                    { "type": "bold", "content": [text("Done")] },
+                    { "type": "line_break" },
+                    { "type": "link", "text": [text("docs")], "url": "https://docs.rs" },
+                ] },
+                { "type": "code_block", "language": null, "content": "plain" },
+                { "type": "task_list", "items": [
+                    { "checked": true, "content": { "type": "paragraph", "content": [text("Ship")] } },
+                ] },
+                { "type": "table",
+                  "headers": [{ "content": [text("A")] }, { "content": [text("B")] }],
+                  "rows": [[{ "content": [text("1")] }, { "content": [text("2")] }]],
+                  "alignments": ["left", "right"] },
+                { "type": "definition_list", "content": [
+                    { "term": [text("Term")], "content": [{ "type": "paragraph", "content": [text("Meaning")] }] },
+                ] },
+                { "type": "horizontal_rule" },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let doc = vec![
+            h2("Nested"),
+            quote((p(vec![italic("quoted")]), ul(["a", "b"]))),
+            Block::BlockList(vec![
+                code_block("rust", "fn main() {}")
+                    .with_title("main.rs")
+                    .with_highlighted([1]),
+                Block::Image {
+                    alt: "logo".into(),
+                    url: "logo.png".into(),
+                },
+            ]),
+            footnote("Source").1,
+            warning(p("Careful")).with_title("Heads up"),
+            details("Log", code_block((), "ok")).with_open(true),
+            table(("A", "B"), [(cell("wide").with_colspan(2),)])
+                .with_footer([(cell(""), "total")])
+                .with_caption("Totals"),
+            p((
+                strikethrough("old"),
+                code("new"),
+                Inline::FootnoteRef("1".into()),
+                Inline::Image {
+                    alt: "icon".into(),
+                    url: "icon.png".into(),
+                },
+            )),
+        ];
+        let json = serde_json::to_string(&doc).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Block>>(&json).unwrap(), doc);
+
+        // Optional fields may be omitted and unknown types are rejected
+        let block: Block = serde_json::from_str(r#"{"type":"code_block","content":"x"}"#).unwrap();
+        assert_eq!(block, code_block((), "x"));
+        let json = serde_json::to_value(code_block((), "x").with_start_line(7)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "type": "code_block", "language": null, "content": "x", "start_line": 7 })
+        );
+        assert!(serde_json::from_str::<Block>(r#"{"type":"video"}"#).is_err());
+
+        // Out-of-range values are normalized the way the builders do it
+        let none = Vec::<Inline>::new;
+        for (json, block) in [
+            (
+                r#"{"type":"heading","level":0,"content":[]}"#,
+                crate::build::h(0, none()),
+            ),
+            (
+                r#"{"type":"heading","level":7,"content":[]}"#,
+                crate::build::h(7, none()),
+            ),
+            (
+                r#"{"type":"table","headers":[{"content":[],"colspan":0}],"rows":[],"alignments":["left"]}"#,
+                table((cell(none()).with_colspan(0),), Vec::<Vec<Cell>>::new()),
+            ),
+        ] {
+            assert_eq!(
+                serde_json::from_str::<Block>(json).unwrap(),
+                block,
+                "{json}"
+            );
+        }
+        let heading = Block::Heading {
+            level: 0,
+            content: vec![],
+            id: None,
+        };
+        let json = serde_json::to_string(&heading).unwrap();
+        assert_eq!(serde_json::from_str::<Block>(&json).unwrap(), h1(none()));
+    }
+}
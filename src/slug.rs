@@ -0,0 +1,80 @@
+//! Heading anchor slugs compatible with GitHub's Markdown renderer.
+
+use std::collections::HashMap;
+
+/// Generates unique heading anchors the way GitHub does.
+///
+/// Text is lowercased, punctuation is dropped and spaces become hyphens.
+/// Repeated slugs get `-1`, `-2`, ... appended in document order.
+///
+/// # Examples
+/// ```rust
+/// use docloom::Slugger;
+///
+/// let mut slugger = Slugger::new();
+/// assert_eq!(slugger.slug("What's New?"), "whats-new");
+/// assert_eq!(slugger.slug("What's New?"), "whats-new-1");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    /// Create a slugger that has not seen any headings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Slug `text`, appending `-1`, `-2`, ... when it was already used.
+    pub fn slug(&mut self, text: &str) -> String {
+        let base = slugify(text);
+        let mut slug = base.clone();
+        while self.occurrences.contains_key(&slug) {
+            let count = self.occurrences.entry(base.clone()).or_default();
+            *count += 1;
+            slug = format!("{base}-{count}");
+        }
+        self.occurrences.insert(slug.clone(), 0);
+        slug
+    }
+
+    /// Mark `id`, such as an explicit heading id, as used, so later slugs
+    /// are numbered around it.
+    pub fn reserve(&mut self, id: &str) {
+        self.occurrences.entry(id.to_string()).or_default();
+    }
+}
+
+/// Lowercase `text`, drop punctuation and turn spaces into hyphens.
+pub(crate) fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '))
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Getting Started"), "getting-started");
+        assert_eq!(slugify("What's new in v0.2?"), "whats-new-in-v02");
+        assert_eq!(slugify("snake_case & kebab-case"), "snake_case--kebab-case");
+        assert_eq!(slugify("Überblick 日本語"), "überblick-日本語");
+    }
+
+    #[test]
+    fn test_slugger_dedupes() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("Usage"), "usage");
+        assert_eq!(slugger.slug("Usage"), "usage-1");
+        assert_eq!(slugger.slug("Usage 1"), "usage-1-1");
+        assert_eq!(slugger.slug("Usage"), "usage-2");
+        slugger.reserve("setup");
+        assert_eq!(slugger.slug("Setup"), "setup-1");
+    }
+}
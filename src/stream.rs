@@ -0,0 +1,147 @@
+//! Streaming rendered output to [`std::io::Write`] targets.
+
+use std::fmt;
+use std::io;
+
+/// Adapter that lets renderers write to an [`io::Write`] target.
+///
+/// Renderers only see [`fmt::Error`], so the adapter keeps the first
+/// [`io::Error`] it hits and [`IoWriter::check`] hands it back.
+///
+/// # Examples
+/// ```rust
+/// use docloom::IoWriter;
+/// use docloom::md::Renderer;
+/// use docloom::prelude::*;
+///
+/// let mut out = IoWriter::new(Vec::new());
+/// Renderer::from_io(&mut out).render_io(&p("Streamed"))?;
+/// assert_eq!(out.into_inner(), b"Streamed\n\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> IoWriter<W> {
+    /// Wrap an [`io::Write`] target.
+    pub fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Borrow the wrapped writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Turn the result of rendering into an [`io::Result`], returning the
+    /// stored I/O error if writing failed.
+    pub fn check(&mut self, result: fmt::Result) -> io::Result<()> {
+        match (result, self.error.take()) {
+            (_, Some(error)) => Err(error),
+            (Ok(()), None) => Ok(()),
+            (Err(fmt::Error), None) => Err(io::Error::other("formatter error")),
+        }
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Render into a buffered [`IoWriter`] around `writer` and flush it.
+pub(crate) fn write_to<W: io::Write>(
+    writer: W,
+    render: impl FnOnce(&mut IoWriter<io::BufWriter<W>>) -> fmt::Result,
+) -> io::Result<()> {
+    let mut out = IoWriter::new(io::BufWriter::new(writer));
+    let result = render(&mut out);
+    out.check(result)?;
+    io::Write::flush(out.get_mut())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::{html, md, term};
+
+    /// Accepts `limit` bytes, then fails every write.
+    struct Full {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl io::Write for Full {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.written.len() + buf.len() > self.limit {
+                return Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"));
+            }
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_to() {
+        let blocks = [h1("Title"), ul(["a", "b"]), table(("x", "y"), [("1", "2")])];
+
+        let mut out = Vec::new();
+        md::doc(blocks.clone()).write_to(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            md::doc(blocks.clone()).to_string()
+        );
+
+        let mut out = Vec::new();
+        term::doc(blocks.clone()).write_to(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            term::doc(blocks.clone()).to_string()
+        );
+
+        let mut out = Vec::new();
+        html::doc(blocks.clone()).write_to(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            html::doc(blocks).to_string()
+        );
+    }
+
+    #[test]
+    fn test_io_errors_surface() {
+        let full = Full {
+            written: Vec::new(),
+            limit: 4,
+        };
+        let mut out = IoWriter::new(full);
+        let error = md::Renderer::from_io(&mut out)
+            .render_io(&p("too long"))
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::StorageFull);
+
+        // Buffered document output reports the error when flushing
+        let full = Full {
+            written: Vec::new(),
+            limit: 4,
+        };
+        let error = term::doc(p("too long")).write_to(full).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::StorageFull);
+    }
+}
@@ -0,0 +1,140 @@
+//! Placement of table cells that span several columns or rows.
+
+use crate::Cell;
+
+/// A cell placed on the table grid, with the part of its span that fits.
+#[derive(Clone, Copy)]
+pub(crate) struct Placed<'a> {
+    pub(crate) cell: &'a Cell,
+    /// Row of the cell's top-left corner.
+    pub(crate) row: usize,
+    /// Column of the cell's top-left corner.
+    pub(crate) column: usize,
+    /// Number of rows covered.
+    pub(crate) rows: usize,
+    /// Number of columns covered.
+    pub(crate) columns: usize,
+}
+
+impl Placed<'_> {
+    /// Whether the cell's top-left corner is at `row` and `column`.
+    pub(crate) fn starts_at(&self, row: usize, column: usize) -> bool {
+        self.row == row && self.column == column
+    }
+}
+
+/// Whether two grid positions are covered by the same cell.
+pub(crate) fn same_cell(a: Option<Placed<'_>>, b: Option<Placed<'_>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.starts_at(b.row, b.column),
+        _ => false,
+    }
+}
+
+/// Number of columns in a table, set by the spans of its header cells.
+pub(crate) fn column_count(headers: &[Cell]) -> usize {
+    headers.iter().map(|cell| cell.colspan.max(1)).sum()
+}
+
+/// Place the cells of `rows` on a grid `columns` wide, recording for every
+/// position the cell that covers it.
+///
+/// Each cell takes the next free position in its row, skipping positions
+/// covered by cells spanning down from earlier rows. Spans are clipped to
+/// the grid and stop at positions that are already covered. Cells that do
+/// not fit are dropped, and positions no cell covers are `None`.
+pub(crate) fn layout(rows: &[Vec<Cell>], columns: usize) -> Vec<Vec<Option<Placed<'_>>>> {
+    let mut grid: Vec<Vec<Option<Placed>>> = vec![vec![None; columns]; rows.len()];
+    for (r, row) in rows.iter().enumerate() {
+        let mut c = 0;
+        for cell in row {
+            while c < columns && grid[r][c].is_some() {
+                c += 1;
+            }
+            if c == columns {
+                break;
+            }
+            let spanned_columns = (c..columns)
+                .take(cell.colspan.max(1))
+                .take_while(|&k| grid[r][k].is_none())
+                .count();
+            let spanned_rows = (r..rows.len())
+                .take(cell.rowspan.max(1))
+                .take_while(|&k| grid[k][c..c + spanned_columns].iter().all(Option::is_none))
+                .count();
+            let placed = Placed {
+                cell,
+                row: r,
+                column: c,
+                rows: spanned_rows,
+                columns: spanned_columns,
+            };
+            for slots in &mut grid[r..r + spanned_rows] {
+                slots[c..c + spanned_columns].fill(Some(placed));
+            }
+            c += spanned_columns;
+        }
+    }
+    grid
+}
+
+/// For each boundary between two columns of a grid row, whether it
+/// separates different cells rather than running through a spanning one.
+pub(crate) fn dividers(row: &[Option<Placed<'_>>]) -> Vec<bool> {
+    row.windows(2)
+        .map(|pair| !same_cell(pair[0], pair[1]))
+        .collect()
+}
+
+/// Cells of a grid in reading order, each once at its top-left corner.
+pub(crate) fn origins<'a>(grid: &[Vec<Option<Placed<'a>>>]) -> impl Iterator<Item = Placed<'a>> {
+    grid.iter().enumerate().flat_map(|(r, row)| {
+        row.iter()
+            .enumerate()
+            .filter_map(move |(c, slot)| slot.filter(|placed| placed.starts_at(r, c)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_layout_spans() {
+        let rows = vec![
+            vec![cell("a").with_rowspan(2), cell("b").with_colspan(2)],
+            vec![cell("c"), cell("d"), cell("dropped")],
+            vec![cell("e").with_colspan(5)],
+        ];
+        let grid = layout(&rows, 3);
+        let origins: Vec<Vec<_>> = grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|slot| slot.map(|p| (p.row, p.column, p.rows, p.columns)))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            origins,
+            [
+                [Some((0, 0, 2, 1)), Some((0, 1, 1, 2)), Some((0, 1, 1, 2))],
+                [Some((0, 0, 2, 1)), Some((1, 1, 1, 1)), Some((1, 2, 1, 1))],
+                [Some((2, 0, 1, 3)), Some((2, 0, 1, 3)), Some((2, 0, 1, 3))],
+            ]
+        );
+        assert_eq!(dividers(&grid[0]), [true, false]);
+        assert_eq!(dividers(&grid[1]), [true, true]);
+        let texts: Vec<_> = super::origins(&grid)
+            .map(|placed| placed.cell.content.clone())
+            .collect();
+        assert_eq!(texts.len(), 5);
+        assert_eq!(texts[4], [text("e")]);
+
+        // Short rows leave positions uncovered
+        let grid = layout(&rows[1..2], 4);
+        assert!(grid[0][3].is_none());
+        assert_eq!(column_count(&[cell("x").with_colspan(2), cell("y")]), 3);
+    }
+}
@@ -30,14 +30,31 @@
 //! ```
 
 use itemize::IntoItems;
-use std::fmt;
-
-use super::{Alignment, Block, Inline, Render, Renderable};
+use std::{borrow::Cow, fmt, io, sync::Arc};
+
+use super::{
+    Alignment, Block, Inline, IoWriter, Numbering, Render, Renderable, Toc, footnote,
+    highlight::{Builtin, Highlighter, Token, TokenKind},
+    stream,
+    table::{self, Placed},
+    width,
+};
+
+mod color;
+mod detect;
+#[cfg(feature = "images")]
+mod image;
+mod theme;
+mod wrap;
+
+pub use color::{Color, ColorDepth, ParseColorError};
+pub use theme::{Callouts, Syntax, Theme, ThemeError};
 
 /// Terminal document wrapper that renders blocks with terminal [`Style`].
 pub struct Doc {
     content: Vec<Block>,
     style: Style,
+    toc: Option<Toc>,
 }
 
 impl Doc {
@@ -46,6 +63,7 @@ impl Doc {
         Self {
             content: value.into_items().collect(),
             style: Style::default(),
+            toc: None,
         }
     }
 
@@ -54,12 +72,54 @@ impl Doc {
         self.style = style;
         self
     }
+
+    /// Stream the document to an [`io::Write`] target such as a file or
+    /// stdout, without building an intermediate string.
+    pub fn write_to(&self, writer: impl io::Write) -> io::Result<()> {
+        stream::write_to(writer, |out| self.render(out))
+    }
+
+    /// Number headings hierarchically before rendering. Any table of
+    /// contents lists the numbered headings.
+    pub fn with_numbering(mut self, numbering: Numbering) -> Self {
+        numbering.apply(&mut self.content);
+        self
+    }
+
+    /// Insert an indented outline of the document's headings.
+    ///
+    /// Accepts a [`crate::TocPosition`] or a configured [`Toc`]; entries are always
+    /// plain text since anchors have no meaning in a terminal.
+    pub fn with_toc(mut self, toc: impl Into<Toc>) -> Self {
+        self.toc = Some(toc.into().links(false));
+        self
+    }
+}
+
+impl Doc {
+    fn render(&self, writer: &mut impl fmt::Write) -> fmt::Result {
+        let mut renderer = Renderer::with_style(writer, self.style.clone());
+        let inserted;
+        let content = match &self.toc {
+            Some(toc) => {
+                inserted = toc.insert(&self.content);
+                &inserted
+            }
+            None => &self.content,
+        };
+        match footnote::collect(content) {
+            Some((body, notes)) => {
+                body.render_with(&mut renderer)?;
+                renderer.write_notes(&notes)
+            }
+            None => content.render_with(&mut renderer),
+        }
+    }
 }
 
 impl fmt::Display for Doc {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.content
-            .render_with(&mut Renderer::with_style(f, self.style))
+        self.render(f)
     }
 }
 
@@ -68,117 +128,163 @@ pub fn doc(value: impl IntoItems<Block>) -> Doc {
     Doc::new(value)
 }
 
+/// Detect the width of the terminal attached to stdout.
+///
+/// Reads the `COLUMNS` environment variable first and falls back to querying
+/// the terminal, returning `None` when neither is available.
+///
+/// # Examples
+/// ```rust
+/// use docloom::term::{Style, terminal_width};
+///
+/// let style = Style::default().width(terminal_width());
+/// ```
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|columns| *columns > 0)
+        .or_else(query_terminal_width)
+}
+
+#[cfg(unix)]
+fn query_terminal_width() -> Option<usize> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    // SAFETY: TIOCGWINSZ only writes a `winsize` into the pointer we pass.
+    let status = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (status == 0 && size.ws_col > 0).then_some(size.ws_col as usize)
+}
+
+#[cfg(not(unix))]
+fn query_terminal_width() -> Option<usize> {
+    None
+}
+
 /// Configuration for terminal rendering style.
-#[derive(Clone, Copy)]
+///
+/// Start from [`Style::default`], [`Style::plain`] or [`Style::ascii`] and
+/// adjust it with the builder methods, so new options can be added without
+/// breaking existing code. A style shares its [`Highlighter`] through an
+/// [`Arc`], so it is `Clone` but not `Copy`; clone it to render with it
+/// more than once.
+#[derive(Clone)]
+#[non_exhaustive]
 pub struct Style {
     /// Whether to emit ANSI color escape codes.
     pub use_colors: bool,
+    /// Colors the terminal can display; richer colors are downgraded to fit.
+    pub color_depth: ColorDepth,
     /// Whether to rely on Unicode box drawing characters.
     pub use_unicode_boxes: bool,
     /// Colors to cycle through for heading levels.
-    pub heading_colors: [&'static str; 6],
+    pub heading_colors: [Color; 6],
     /// Foreground color used for code blocks.
-    pub code_color: &'static str,
+    pub code_color: Color,
     /// Background color used for code blocks.
-    pub code_bg: &'static str,
+    pub code_bg: Color,
     /// Color used for rendering hyperlinks.
-    pub link_color: &'static str,
+    pub link_color: Color,
     /// Color used for list markers.
-    pub list_color: &'static str,
+    pub list_color: Color,
     /// Color used for borders such as code block boxes.
-    pub border_color: &'static str,
+    pub border_color: Color,
+    /// Color used for table header cells.
+    pub table_header_color: Color,
+    /// Character drawn left of blockquotes, instead of `▎` or `|`.
+    pub quote_border: Option<char>,
+    /// Colors of highlighted code.
+    pub syntax: Syntax,
+    /// Colors of callout boxes, by kind.
+    pub callouts: Callouts,
+    /// Highlighter for code blocks with a language, if any.
+    pub highlighter: Option<Arc<dyn Highlighter + Send + Sync>>,
+    /// Whether to emit OSC 8 hyperlinks that terminals make clickable.
+    pub hyperlinks: bool,
+    /// How [`Block::Image`] nodes pointing at local files are drawn.
+    pub image_mode: ImageMode,
+    /// Maximum width, in columns, of images drawn as graphics.
+    pub image_width: usize,
+    /// Column at which paragraph, list and quote text is wrapped, if any.
+    pub width: Option<usize>,
 }
 
 impl Default for Style {
     fn default() -> Self {
+        let theme = Theme::dark();
         Self {
             use_colors: true,
+            color_depth: ColorDepth::TrueColor,
             use_unicode_boxes: true,
-            heading_colors: [
-                Style::BRIGHT_CYAN,
-                Style::CYAN,
-                Style::BRIGHT_BLUE,
-                Style::BLUE,
-                Style::BRIGHT_WHITE,
-                Style::BRIGHT_WHITE,
-            ],
-            code_color: Style::GREEN,
-            code_bg: Style::BG_BLACK,
-            link_color: Style::BRIGHT_BLUE,
-            list_color: Style::BRIGHT_YELLOW,
-            border_color: Style::BRIGHT_BLACK,
+            heading_colors: theme.heading_colors,
+            code_color: theme.code_color,
+            code_bg: theme.code_bg,
+            link_color: theme.link_color,
+            list_color: theme.list_color,
+            border_color: theme.border_color,
+            table_header_color: theme.table_header_color,
+            quote_border: theme.quote_border,
+            syntax: theme.syntax,
+            callouts: theme.callouts,
+            highlighter: Some(Arc::new(Builtin)),
+            hyperlinks: false,
+            image_mode: ImageMode::Placeholder,
+            image_width: 40,
+            width: None,
         }
     }
 }
 
+/// Strategies for drawing images in the terminal.
+///
+/// Modes other than [`ImageMode::Placeholder`] require the `images` feature,
+/// a local PNG or JPEG file and colors enabled; anything else falls back to
+/// the placeholder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImageMode {
+    /// Show the alt text and URL as a styled placeholder.
+    Placeholder,
+    /// Draw pixels with Unicode half-block characters at the style's color depth.
+    HalfBlock,
+    /// Emit the kitty terminal graphics protocol.
+    Kitty,
+    /// Emit DEC sixel graphics.
+    Sixel,
+}
+
 impl Style {
-    /// ANSI escape code for a black foreground.
-    pub const BLACK: &str = "\x1b[30m";
-    /// ANSI escape code for a red foreground.
-    pub const RED: &str = "\x1b[31m";
-    /// ANSI escape code for a green foreground.
-    pub const GREEN: &str = "\x1b[32m";
-    /// ANSI escape code for a yellow foreground.
-    pub const YELLOW: &str = "\x1b[33m";
-    /// ANSI escape code for a blue foreground.
-    pub const BLUE: &str = "\x1b[34m";
-    /// ANSI escape code for a magenta foreground.
-    pub const MAGENTA: &str = "\x1b[35m";
-    /// ANSI escape code for a cyan foreground.
-    pub const CYAN: &str = "\x1b[36m";
-    /// ANSI escape code for a white foreground.
-    pub const WHITE: &str = "\x1b[37m";
-
-    /// ANSI escape code for a bright black foreground.
-    pub const BRIGHT_BLACK: &str = "\x1b[90m";
-    /// ANSI escape code for a bright red foreground.
-    pub const BRIGHT_RED: &str = "\x1b[91m";
-    /// ANSI escape code for a bright green foreground.
-    pub const BRIGHT_GREEN: &str = "\x1b[92m";
-    /// ANSI escape code for a bright yellow foreground.
-    pub const BRIGHT_YELLOW: &str = "\x1b[93m";
-    /// ANSI escape code for a bright blue foreground.
-    pub const BRIGHT_BLUE: &str = "\x1b[94m";
-    /// ANSI escape code for a bright magenta foreground.
-    pub const BRIGHT_MAGENTA: &str = "\x1b[95m";
-    /// ANSI escape code for a bright cyan foreground.
-    pub const BRIGHT_CYAN: &str = "\x1b[96m";
-    /// ANSI escape code for a bright white foreground.
-    pub const BRIGHT_WHITE: &str = "\x1b[97m";
-
-    /// ANSI escape code for a black background.
-    pub const BG_BLACK: &str = "\x1b[40m";
-    /// ANSI escape code for a red background.
-    pub const BG_RED: &str = "\x1b[41m";
-    /// ANSI escape code for a green background.
-    pub const BG_GREEN: &str = "\x1b[42m";
-    /// ANSI escape code for a yellow background.
-    pub const BG_YELLOW: &str = "\x1b[43m";
-    /// ANSI escape code for a blue background.
-    pub const BG_BLUE: &str = "\x1b[44m";
-    /// ANSI escape code for a magenta background.
-    pub const BG_MAGENTA: &str = "\x1b[45m";
-    /// ANSI escape code for a cyan background.
-    pub const BG_CYAN: &str = "\x1b[46m";
-    /// ANSI escape code for a white background.
-    pub const BG_WHITE: &str = "\x1b[47m";
-
-    /// ANSI escape code for a bright black background.
-    pub const BG_BRIGHT_BLACK: &str = "\x1b[100m";
-    /// ANSI escape code for a bright red background.
-    pub const BG_BRIGHT_RED: &str = "\x1b[101m";
-    /// ANSI escape code for a bright green background.
-    pub const BG_BRIGHT_GREEN: &str = "\x1b[102m";
-    /// ANSI escape code for a bright yellow background.
-    pub const BG_BRIGHT_YELLOW: &str = "\x1b[103m";
-    /// ANSI escape code for a bright blue background.
-    pub const BG_BRIGHT_BLUE: &str = "\x1b[104m";
-    /// ANSI escape code for a bright magenta background.
-    pub const BG_BRIGHT_MAGENTA: &str = "\x1b[105m";
-    /// ANSI escape code for a bright cyan background.
-    pub const BG_BRIGHT_CYAN: &str = "\x1b[106m";
-    /// ANSI escape code for a bright white background.
-    pub const BG_BRIGHT_WHITE: &str = "\x1b[107m";
+    /// The black terminal color.
+    pub const BLACK: Color = Color::Black;
+    /// The red terminal color.
+    pub const RED: Color = Color::Red;
+    /// The green terminal color.
+    pub const GREEN: Color = Color::Green;
+    /// The yellow terminal color.
+    pub const YELLOW: Color = Color::Yellow;
+    /// The blue terminal color.
+    pub const BLUE: Color = Color::Blue;
+    /// The magenta terminal color.
+    pub const MAGENTA: Color = Color::Magenta;
+    /// The cyan terminal color.
+    pub const CYAN: Color = Color::Cyan;
+    /// The white terminal color.
+    pub const WHITE: Color = Color::White;
+
+    /// The bright black terminal color.
+    pub const BRIGHT_BLACK: Color = Color::BrightBlack;
+    /// The bright red terminal color.
+    pub const BRIGHT_RED: Color = Color::BrightRed;
+    /// The bright green terminal color.
+    pub const BRIGHT_GREEN: Color = Color::BrightGreen;
+    /// The bright yellow terminal color.
+    pub const BRIGHT_YELLOW: Color = Color::BrightYellow;
+    /// The bright blue terminal color.
+    pub const BRIGHT_BLUE: Color = Color::BrightBlue;
+    /// The bright magenta terminal color.
+    pub const BRIGHT_MAGENTA: Color = Color::BrightMagenta;
+    /// The bright cyan terminal color.
+    pub const BRIGHT_CYAN: Color = Color::BrightCyan;
+    /// The bright white terminal color.
+    pub const BRIGHT_WHITE: Color = Color::BrightWhite;
 
     /// ANSI escape code to reset all text attributes.
     pub const RESET: &str = "\x1b[0m";
@@ -215,6 +321,12 @@ impl Style {
         self
     }
 
+    /// Downgrade colors to what a terminal with `color_depth` can display.
+    pub fn color_depth(mut self, color_depth: ColorDepth) -> Self {
+        self.color_depth = color_depth;
+        self
+    }
+
     /// Enable or disable Unicode box-drawing characters.
     pub fn unicode_boxes(mut self, use_unicode_boxes: bool) -> Self {
         self.use_unicode_boxes = use_unicode_boxes;
@@ -222,45 +334,102 @@ impl Style {
     }
 
     /// Override the set of heading colors.
-    pub fn heading_colors(mut self, heading_colors: [&'static str; 6]) -> Self {
+    pub fn heading_colors(mut self, heading_colors: [Color; 6]) -> Self {
         self.heading_colors = heading_colors;
         self
     }
 
     /// Set the code foreground color.
-    pub fn code_color(mut self, code_color: &'static str) -> Self {
+    pub fn code_color(mut self, code_color: Color) -> Self {
         self.code_color = code_color;
         self
     }
 
     /// Set the code background color.
-    pub fn code_bg(mut self, code_bg: &'static str) -> Self {
+    pub fn code_bg(mut self, code_bg: Color) -> Self {
         self.code_bg = code_bg;
         self
     }
 
     /// Set the hyperlink color.
-    pub fn link_color(mut self, link_color: &'static str) -> Self {
+    pub fn link_color(mut self, link_color: Color) -> Self {
         self.link_color = link_color;
         self
     }
 
     /// Set the list marker color.
-    pub fn list_color(mut self, list_color: &'static str) -> Self {
+    pub fn list_color(mut self, list_color: Color) -> Self {
         self.list_color = list_color;
         self
     }
 
     /// Set the border color used for code blocks and tables.
-    pub fn border_color(mut self, border_color: &'static str) -> Self {
+    pub fn border_color(mut self, border_color: Color) -> Self {
         self.border_color = border_color;
         self
     }
+
+    /// Set the table header color.
+    pub fn table_header_color(mut self, table_header_color: Color) -> Self {
+        self.table_header_color = table_header_color;
+        self
+    }
+
+    /// Draw blockquote borders with `quote_border`, or the default with `None`.
+    pub fn quote_border(mut self, quote_border: impl Into<Option<char>>) -> Self {
+        self.quote_border = quote_border.into();
+        self
+    }
+
+    /// Set the colors of highlighted code.
+    pub fn syntax(mut self, syntax: Syntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+
+    /// Set the colors of callout boxes.
+    pub fn callouts(mut self, callouts: Callouts) -> Self {
+        self.callouts = callouts;
+        self
+    }
+
+    /// Highlight code blocks with `highlighter`, or disable highlighting
+    /// with `None`.
+    pub fn highlighter(mut self, highlighter: Option<Arc<dyn Highlighter + Send + Sync>>) -> Self {
+        self.highlighter = highlighter;
+        self
+    }
+
+    /// Enable or disable OSC 8 hyperlinks.
+    pub fn hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+
+    /// Set how images are drawn.
+    pub fn image_mode(mut self, image_mode: ImageMode) -> Self {
+        self.image_mode = image_mode;
+        self
+    }
+
+    /// Set the maximum width, in columns, of images drawn as graphics.
+    pub fn image_width(mut self, image_width: usize) -> Self {
+        self.image_width = image_width;
+        self
+    }
+
+    /// Wrap text at `width` columns, or disable wrapping with `None`.
+    pub fn width(mut self, width: impl Into<Option<usize>>) -> Self {
+        self.width = width.into();
+        self
+    }
 }
 
 pub struct Renderer<'a, W> {
     writer: &'a mut W,
     indent_level: usize,
+    /// Number of enclosing list items, used to vary bullet markers.
+    list_depth: usize,
     style: Style,
 }
 
@@ -275,6 +444,7 @@ impl<'a, W> Renderer<'a, W> {
         Self {
             writer,
             indent_level: 0,
+            list_depth: 0,
             style,
         }
     }
@@ -289,6 +459,45 @@ impl<'a, W> Renderer<'a, W> {
     fn color(&self, code: &'static str) -> &'static str {
         if self.style.use_colors { code } else { "" }
     }
+
+    /// Escape code setting the foreground to `color`, downgraded to fit.
+    fn fg(&self, color: Color) -> String {
+        if self.style.use_colors {
+            color.downgrade(self.style.color_depth).fg()
+        } else {
+            String::new()
+        }
+    }
+
+    /// Escape code setting the background to `color`, downgraded to fit.
+    fn bg(&self, color: Color) -> String {
+        if self.style.use_colors {
+            color.downgrade(self.style.color_depth).bg()
+        } else {
+            String::new()
+        }
+    }
+}
+
+impl<'a, W: io::Write> Renderer<'a, IoWriter<W>> {
+    /// Create a renderer that streams to the [`io::Write`] target in `writer`.
+    pub fn from_io(writer: &'a mut IoWriter<W>) -> Self {
+        Self::new(writer)
+    }
+
+    /// Create a streaming renderer with a custom [`Style`].
+    pub fn from_io_with_style(writer: &'a mut IoWriter<W>, style: Style) -> Self {
+        Self::with_style(writer, style)
+    }
+
+    /// Render a value, returning any error from the underlying writer.
+    pub fn render_io<R>(&mut self, r: &R) -> io::Result<()>
+    where
+        R: for<'b> Renderable<Renderer<'b, IoWriter<W>>, Output = Result<(), fmt::Error>> + ?Sized,
+    {
+        let result = r.render_with(self);
+        self.writer.check(result)
+    }
 }
 
 impl Renderer<'_, String> {
@@ -321,15 +530,27 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
 
         match inner {
             Paragraph(content) => {
-                self.write_indent()?;
-                content.render_with(self)?;
-                writeln!(self.writer, "{}", self.color(Style::RESET))
+                // Render first so the text can be wrapped as a whole
+                let mut rendered = String::new();
+                content
+                    .render_with(&mut Renderer::with_style(&mut rendered, self.style.clone()))?;
+                let lines = match self.style.width {
+                    Some(width) => {
+                        wrap::wrap(&rendered, width.saturating_sub(self.indent_level * 2))
+                    }
+                    None => rendered.split('\n').map(String::from).collect(),
+                };
+                for line in lines {
+                    self.write_indent()?;
+                    writeln!(self.writer, "{}{}", line, self.color(Style::RESET))?;
+                }
+                Ok(())
             }
 
-            Heading { level, content } => {
+            Heading { level, content, .. } => {
                 writeln!(self.writer)?;
                 self.write_indent()?;
-                let level_idx = (*level as usize - 1).min(5);
+                let level_idx = (*level).clamp(1, 6) as usize - 1;
                 let color = self.style.heading_colors[level_idx];
                 let prefix = if self.style.use_unicode_boxes {
                     ["█ ", "▓ ", "▒ ", "░ ", "• ", "• "][level_idx]
@@ -341,7 +562,7 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                     self.writer,
                     "{}{}{}",
                     self.color(Style::BOLD),
-                    self.color(color),
+                    self.fg(color),
                     prefix
                 )?;
                 content.render_with(self)?;
@@ -349,51 +570,91 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 writeln!(self.writer)
             }
 
-            CodeBlock { language, content } => {
+            CodeBlock {
+                language,
+                content,
+                meta,
+            } => {
                 writeln!(self.writer)?;
                 self.write_indent()?;
 
-                let (top, left, bottom) = if self.style.use_unicode_boxes {
-                    ("┌─", "│", "└─")
+                let (top, left, bottom, mark) = if self.style.use_unicode_boxes {
+                    ("┌─", "│", "└─", "▶")
                 } else {
-                    ("+--", "|", "+--")
+                    ("+--", "|", "+--", ">")
                 };
 
-                // Top border
-                if let Some(lang) = language {
-                    writeln!(
-                        self.writer,
-                        "{}{}{}[ {} ]{}",
-                        self.color(Style::DIM),
-                        self.color(self.style.border_color),
-                        top,
-                        lang,
-                        self.color(Style::RESET)
-                    )?;
-                } else {
-                    writeln!(
+                // Top border, followed by the title
+                write!(
+                    self.writer,
+                    "{}{}{}",
+                    self.color(Style::DIM),
+                    self.fg(self.style.border_color),
+                    top
+                )?;
+                match language {
+                    Some(lang) => write!(self.writer, "[ {} ]", lang)?,
+                    None => write!(self.writer, "────")?,
+                }
+                write!(self.writer, "{}", self.color(Style::RESET))?;
+                if let Some(title) = &meta.title {
+                    write!(
                         self.writer,
-                        "{}{}{}────{}",
-                        self.color(Style::DIM),
-                        self.color(self.style.border_color),
-                        top,
+                        " {}{}{}",
+                        self.color(Style::BOLD),
+                        title,
                         self.color(Style::RESET)
                     )?;
                 }
+                writeln!(self.writer)?;
 
-                // Content
-                for line in content.to_string().lines() {
+                // Content, with a gutter for line numbers and highlighted lines
+                let lines = self.highlight(language.as_deref(), content);
+                let first = meta.start_line.unwrap_or(1);
+                let gutter = meta.start_line.is_some() || !meta.highlighted.is_empty();
+                let number_width = match meta.start_line {
+                    Some(_) => (first + lines.len().saturating_sub(1)).to_string().len(),
+                    None => 0,
+                };
+                let rule = if meta.start_line.is_some() { left } else { " " };
+                for (i, line) in lines.iter().enumerate() {
                     self.write_indent()?;
-                    writeln!(
+                    write!(
                         self.writer,
-                        "{}{}{}{} {}{}",
+                        "{}{}{}{} ",
                         self.color(Style::DIM),
-                        self.color(self.style.border_color),
+                        self.fg(self.style.border_color),
                         left,
-                        self.color(Style::RESET),
-                        self.color(self.style.code_color),
-                        line
+                        self.color(Style::RESET)
                     )?;
+                    let number = first + i;
+                    let label = match meta.start_line {
+                        Some(_) => format!("{number:>number_width$} "),
+                        None => String::new(),
+                    };
+                    if gutter && meta.highlighted.contains(&number) {
+                        write!(
+                            self.writer,
+                            "{}{}{}{}{} {}",
+                            self.color(Style::BOLD),
+                            self.fg(self.style.list_color),
+                            label,
+                            mark,
+                            self.color(Style::RESET),
+                            self.color(Style::BOLD)
+                        )?;
+                    } else if gutter {
+                        write!(
+                            self.writer,
+                            "{}{}{}{}{} ",
+                            self.color(Style::DIM),
+                            self.fg(self.style.border_color),
+                            label,
+                            rule,
+                            self.color(Style::RESET)
+                        )?;
+                    }
+                    writeln!(self.writer, "{line}")?;
                 }
 
                 // Bottom border
@@ -402,7 +663,7 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                     self.writer,
                     "{}{}{}────{}",
                     self.color(Style::DIM),
-                    self.color(self.style.border_color),
+                    self.fg(self.style.border_color),
                     bottom,
                     self.color(Style::RESET)
                 )?;
@@ -410,24 +671,28 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
             }
 
             List { ordered, items } => {
+                // Numbers are right-aligned so item text lines up past nine
+                let number_width = items.len().to_string().len();
+                let bullets = if self.style.use_unicode_boxes {
+                    ["•", "◦", "▪"]
+                } else {
+                    ["*", "-", "+"]
+                };
                 for (idx, item) in items.iter().enumerate() {
                     self.write_indent()?;
-                    let marker = match (ordered, self.style.use_unicode_boxes) {
-                        (true, _) => format!("{}.", idx + 1),
-                        (false, true) => String::from("•"),
-                        (false, false) => String::from("*"),
+                    let marker = if *ordered {
+                        format!("{:>number_width$}.", idx + 1)
+                    } else {
+                        bullets[self.list_depth % bullets.len()].to_string()
                     };
                     write!(
                         self.writer,
                         "{}{} {}",
-                        self.color(self.style.list_color),
+                        self.fg(self.style.list_color),
                         marker,
                         self.color(Style::RESET)
                     )?;
-
-                    // self.indent_level += 1;
-                    item.render_with(self)?;
-                    // self.indent_level -= 1;
+                    self.write_item(wrap::visible_width(&marker) + 1, std::slice::from_ref(item))?;
                 }
                 writeln!(self.writer)
             }
@@ -446,14 +711,14 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                     write!(
                         self.writer,
                         "{}{} {}",
-                        self.color(self.style.list_color),
+                        self.fg(self.style.list_color),
                         box_char,
                         self.color(Style::RESET)
                     )?;
-
-                    // self.indent_level += 1;
-                    item.render_with(self)?;
-                    // self.indent_level -= 1;
+                    self.write_item(
+                        wrap::visible_width(box_char) + 1,
+                        std::slice::from_ref(item),
+                    )?;
                 }
                 writeln!(self.writer)
             }
@@ -462,163 +727,98 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 headers,
                 rows,
                 alignments,
+                footer,
+                caption,
             } => {
-                // Calculate column widths
-                let col_count = headers.len();
-                let mut widths: Vec<usize> =
-                    headers.iter().map(|h| Self::measure_inline(h)).collect();
-
-                for row in rows {
-                    for (i, cell) in row.iter().enumerate().take(col_count) {
-                        widths[i] = widths[i].max(Self::measure_inline(cell));
+                let columns = table::column_count(headers);
+                let sections = [std::slice::from_ref(headers), rows, footer]
+                    .map(|section| table::layout(section, columns));
+                // Render cells first, so links can carry hyperlink escapes
+                let texts = sections.each_ref().map(|grid| self.cell_texts(grid));
+
+                // Calculate column widths, widening the last column under a
+                // spanning cell when its text needs more room
+                let mut widths = vec![3; columns];
+                for (grid, texts) in sections.iter().zip(&texts) {
+                    for placed in table::origins(grid).filter(|p| p.columns == 1) {
+                        let width = wrap::visible_width(&texts[placed.row][placed.column]);
+                        widths[placed.column] = widths[placed.column].max(width);
                     }
                 }
-
-                for w in &mut widths {
-                    *w = (*w).max(3);
-                }
-
-                let (tl, tr, bl, br, h, v, cross, t_down, t_up, t_left, t_right) =
-                    if self.style.use_unicode_boxes {
-                        ("┌", "┐", "└", "┘", "─", "│", "┼", "┬", "┴", "├", "┤")
-                    } else {
-                        ("+", "+", "+", "+", "-", "|", "+", "+", "+", "+", "+")
-                    };
-
-                // Top border
-                self.write_indent()?;
-                write!(
-                    self.writer,
-                    "{}{}{}",
-                    self.color(Style::DIM),
-                    self.color(self.style.border_color),
-                    tl
-                )?;
-                for (i, w) in widths.iter().enumerate() {
-                    write!(self.writer, "{}", h.repeat(w + 2))?;
-                    if i < widths.len() - 1 {
-                        write!(self.writer, "{}", t_down)?;
+                for (grid, texts) in sections.iter().zip(&texts) {
+                    for placed in table::origins(grid).filter(|p| p.columns > 1) {
+                        let width = wrap::visible_width(&texts[placed.row][placed.column]);
+                        let spanned = Self::spanned_width(&widths, &placed);
+                        widths[placed.column + placed.columns - 1] += width.saturating_sub(spanned);
                     }
                 }
-                writeln!(self.writer, "{}{}", tr, self.color(Style::RESET))?;
 
-                // Header row
-                self.write_indent()?;
-                write!(
-                    self.writer,
-                    "{}{}{}{}",
-                    self.color(Style::DIM),
-                    self.color(self.style.border_color),
-                    v,
-                    self.color(Style::RESET)
-                )?;
-                for (i, h_cell) in headers.iter().enumerate() {
-                    write!(
+                if let Some(caption) = caption {
+                    self.write_indent()?;
+                    writeln!(
                         self.writer,
-                        " {}{}",
+                        "{}{}{}",
                         self.color(Style::BOLD),
-                        self.color(Style::BRIGHT_CYAN)
-                    )?;
-                    let rendered = Self::to_plain_string(h_cell);
-                    let align = alignments.get(i).copied().unwrap_or(Alignment::Left);
-                    let aligned = Self::align_text(&rendered, widths[i], align);
-                    write!(self.writer, "{}", aligned)?;
-                    write!(
-                        self.writer,
-                        "{} {}{}{}{}",
-                        self.color(Style::RESET),
-                        self.color(Style::DIM),
-                        self.color(self.style.border_color),
-                        v,
+                        caption,
                         self.color(Style::RESET)
                     )?;
                 }
-                writeln!(self.writer)?;
 
-                // Separator row
-                self.write_indent()?;
-                write!(
-                    self.writer,
-                    "{}{}{}",
-                    self.color(Style::DIM),
-                    self.color(self.style.border_color),
-                    t_left
-                )?;
-                for (i, w) in widths.iter().enumerate() {
-                    write!(self.writer, "{}", h.repeat(w + 2))?;
-                    if i < widths.len() - 1 {
-                        write!(self.writer, "{}", cross)?;
-                    }
-                }
-                writeln!(self.writer, "{}{}", t_right, self.color(Style::RESET))?;
-
-                // Body rows
-                for row in rows {
-                    self.write_indent()?;
-                    write!(
-                        self.writer,
-                        "{}{}{}{}",
-                        self.color(Style::DIM),
-                        self.color(self.style.border_color),
-                        v,
-                        self.color(Style::RESET)
-                    )?;
-                    for (i, w) in widths.iter().enumerate() {
-                        write!(self.writer, " ")?;
-                        let align = alignments.get(i).copied().unwrap_or(Alignment::Left);
-                        if let Some(cell) = row.get(i) {
-                            let rendered = Self::to_plain_string(cell);
-                            let aligned = Self::align_text(&rendered, *w, align);
-                            write!(self.writer, "{}", aligned)?;
-                        } else {
-                            write!(self.writer, "{:width$}", "", width = *w)?;
+                // Fit the table into the available width, if one is set
+                if let Some(max) = self.style.width {
+                    let max = max.saturating_sub(self.indent_level * 2);
+                    match Self::fit_columns(&widths, max) {
+                        Some(fitted) => widths = fitted,
+                        None => {
+                            let [head, body, foot] = &sections;
+                            let body = Self::body_records(&head[0], &texts[0][0], body, &texts[1]);
+                            let foot = Self::footer_records(foot, &texts[2]);
+                            return self.write_records(&body, &foot);
                         }
-                        write!(
-                            self.writer,
-                            " {}{}{}{}",
-                            self.color(Style::DIM),
-                            self.color(self.style.border_color),
-                            v,
-                            self.color(Style::RESET)
-                        )?;
                     }
-                    writeln!(self.writer)?;
                 }
 
-                // Bottom border
-                self.write_indent()?;
-                write!(
-                    self.writer,
-                    "{}{}{}",
-                    self.color(Style::DIM),
-                    self.color(self.style.border_color),
-                    bl
-                )?;
-                for (i, w) in widths.iter().enumerate() {
-                    write!(self.writer, "{}", h.repeat(w + 2))?;
-                    if i < widths.len() - 1 {
-                        write!(self.writer, "{}", t_up)?;
-                    }
+                let [head, body, foot] = &sections;
+                let across = vec![true; columns];
+                let mut last = table::dividers(&head[0]);
+                self.write_table_border(&widths, None, Some(&last), &across)?;
+                self.write_table_section(head, &texts[0], &widths, alignments, true)?;
+                // An empty body keeps the header's column lines
+                let below = body
+                    .first()
+                    .map_or(last.clone(), |row| table::dividers(row));
+                self.write_table_border(&widths, Some(&last), Some(&below), &across)?;
+                self.write_table_section(body, &texts[1], &widths, alignments, false)?;
+                if let Some(row) = body.last() {
+                    last = table::dividers(row);
+                }
+                if let Some(first) = foot.first() {
+                    let below = table::dividers(first);
+                    self.write_table_border(&widths, Some(&last), Some(&below), &across)?;
+                    self.write_table_section(foot, &texts[2], &widths, alignments, false)?;
+                    last = table::dividers(&foot[foot.len() - 1]);
                 }
-                writeln!(self.writer, "{}{}", br, self.color(Style::RESET))?;
+                self.write_table_border(&widths, Some(&last), None, &across)?;
                 writeln!(self.writer)
             }
 
             Blockquote(inner) => {
                 // Draw left border and indent content
-                let border = if self.style.use_unicode_boxes {
-                    "▎"
-                } else {
-                    "|"
+                let border = match self.style.quote_border {
+                    Some(border) => border,
+                    None if self.style.use_unicode_boxes => '▎',
+                    None => '|',
                 };
 
                 // Render each block individually with proper indentation
+                let style = self.narrowed(width::width(border.encode_utf8(&mut [0; 4])) + 1);
                 for block in inner.iter() {
                     // Render the block to a string first
                     let mut block_content = String::new();
-                    self.indent_level += 1;
-                    block.render_with(&mut Renderer::with_style(&mut block_content, self.style))?;
-                    self.indent_level -= 1;
+                    block.render_with(&mut Renderer::with_style(
+                        &mut block_content,
+                        style.clone(),
+                    ))?;
 
                     // Add the border to each line of the block
                     for line in block_content.lines() {
@@ -627,7 +827,7 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                             self.writer,
                             "{}{}{}{} {}",
                             self.color(Style::DIM),
-                            self.color(self.style.border_color),
+                            self.fg(self.style.border_color),
                             border,
                             self.color(Style::RESET),
                             line
@@ -637,7 +837,77 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 }
                 writeln!(self.writer)
             }
-            Image { alt: _, url: _ } => unimplemented!(),
+            Callout {
+                kind,
+                title,
+                content,
+            } => {
+                // A box open on the right, like code blocks, in the kind's color
+                let color = self.fg(self.style.callouts.color(*kind));
+                let (top, left, bottom) = if self.style.use_unicode_boxes {
+                    ("┌─", "│", "└─")
+                } else {
+                    ("+--", "|", "+--")
+                };
+
+                writeln!(self.writer)?;
+                self.write_indent()?;
+                writeln!(
+                    self.writer,
+                    "{}{} {}{}{}",
+                    color,
+                    top,
+                    self.color(Style::BOLD),
+                    title.as_deref().unwrap_or(kind.label()),
+                    self.color(Style::RESET)
+                )?;
+                // Blank lines around the content would leave gaps in the box
+                let mut rendered = String::new();
+                let style = self.narrowed(width::width(left) + 1);
+                content.render_with(&mut Renderer::with_style(&mut rendered, style))?;
+                for line in rendered.trim_matches('\n').lines() {
+                    self.write_indent()?;
+                    writeln!(
+                        self.writer,
+                        "{}{}{} {}",
+                        color,
+                        left,
+                        self.color(Style::RESET),
+                        line
+                    )?;
+                }
+                self.write_indent()?;
+                writeln!(
+                    self.writer,
+                    "{}{}────{}",
+                    color,
+                    bottom,
+                    self.color(Style::RESET)
+                )?;
+                writeln!(self.writer)
+            }
+            Image { alt, url } => {
+                // Graphics are escape sequences too, so plain output keeps the placeholder
+                #[cfg(feature = "images")]
+                if self.style.use_colors
+                    && let Some(graphic) = image::load(
+                        url,
+                        self.style.image_mode,
+                        self.style.image_width,
+                        self.style.color_depth,
+                    )
+                {
+                    for line in graphic.lines() {
+                        self.write_indent()?;
+                        writeln!(self.writer, "{line}")?;
+                    }
+                    return writeln!(self.writer);
+                }
+                self.write_indent()?;
+                self.write_image_placeholder(alt, url)?;
+                writeln!(self.writer)?;
+                writeln!(self.writer)
+            }
             HorizontalRule => {
                 self.write_indent()?;
                 let rule = if self.style.use_unicode_boxes {
@@ -649,7 +919,7 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                     self.writer,
                     "{}{}{}{}",
                     self.color(Style::DIM),
-                    self.color(self.style.border_color),
+                    self.fg(self.style.border_color),
                     rule.repeat(50),
                     self.color(Style::RESET)
                 )?;
@@ -661,6 +931,60 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 }
                 Ok(())
             }
+            FootnoteDefinition { label, content } => {
+                self.write_indent()?;
+                let marker = self.footnote_marker(label);
+                write!(
+                    self.writer,
+                    "{}{} {}",
+                    self.fg(self.style.link_color),
+                    marker,
+                    self.color(Style::RESET)
+                )?;
+                self.write_item(wrap::visible_width(&marker) + 1, content)
+            }
+            DefinitionList(items) => {
+                // Bold terms over indented definitions, as in man pages
+                for (i, (term, definition)) in items.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(self.writer)?;
+                    }
+                    self.write_indent()?;
+                    write!(self.writer, "{}", self.color(Style::BOLD))?;
+                    term.render_with(self)?;
+                    writeln!(self.writer, "{}", self.color(Style::RESET))?;
+                    self.write_indented(4, definition)?;
+                }
+                writeln!(self.writer)
+            }
+            Details {
+                summary,
+                content,
+                open,
+            } => {
+                // Closed sections show only their summary
+                let marker = match (self.style.use_unicode_boxes, *open) {
+                    (true, true) => "▾",
+                    (true, false) => "▸",
+                    (false, true) => "v",
+                    (false, false) => ">",
+                };
+                self.write_indent()?;
+                write!(
+                    self.writer,
+                    "{}{} {}{}",
+                    self.fg(self.style.list_color),
+                    marker,
+                    self.color(Style::RESET),
+                    self.color(Style::BOLD)
+                )?;
+                summary.render_with(self)?;
+                writeln!(self.writer, "{}", self.color(Style::RESET))?;
+                if *open {
+                    self.write_indented(wrap::visible_width(marker) + 1, content)?;
+                }
+                writeln!(self.writer)
+            }
         }
     }
 
@@ -696,8 +1020,8 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                 write!(
                     self.writer,
                     "{}{}{}{}{}",
-                    self.color(self.style.code_bg),
-                    self.color(self.style.code_color),
+                    self.bg(self.style.code_bg),
+                    self.fg(self.style.code_color),
                     text,
                     self.color(Style::RESET),
                     self.color(Style::RESET)
@@ -709,74 +1033,648 @@ impl<'a, W: fmt::Write> Render for Renderer<'a, W> {
                     self.writer,
                     "{}{}",
                     self.color(Style::UNDERLINE),
-                    self.color(self.style.link_color)
+                    self.fg(self.style.link_color)
                 )?;
+                // Heading anchors mean nothing in a terminal, so only the
+                // text of cross-references is shown
+                if url.starts_with('#') {
+                    text.render_with(self)?;
+                    return write!(self.writer, "{}", self.color(Style::RESET));
+                }
+                let url = clean_url(url);
+                // The text itself is clickable, so the URL is not repeated
+                if self.style.hyperlinks {
+                    write!(self.writer, "\x1b]8;;{url}\x1b\\")?;
+                    text.render_with(self)?;
+                    write!(self.writer, "\x1b]8;;\x1b\\")?;
+                    return write!(self.writer, "{}", self.color(Style::RESET));
+                }
                 text.render_with(self)?;
                 write!(
                     self.writer,
                     "{} {}{}({}){}",
                     self.color(Style::RESET),
                     self.color(Style::DIM),
-                    self.color(self.style.border_color),
+                    self.fg(self.style.border_color),
                     url,
                     self.color(Style::RESET)
                 )?;
                 Ok(())
             }
-            Image { alt: _, url: _ } => unimplemented!(),
+            Image { alt, url } => self.write_image_placeholder(alt, url),
             LineBreak => {
                 writeln!(self.writer)?;
                 self.write_indent()
             }
+            FootnoteRef(label) => write!(
+                self.writer,
+                "{}{}{}",
+                self.fg(self.style.link_color),
+                self.footnote_marker(label),
+                self.color(Style::RESET)
+            ),
         }
     }
 }
 
 // Helper methods
 impl<'a, W: fmt::Write> Renderer<'a, W> {
-    fn align_text(text: &str, width: usize, align: Alignment) -> String {
-        let text_len = text.chars().count();
-        if text_len >= width {
-            text.to_string()
+    /// Style for nested content that loses `used` columns to a prefix.
+    fn narrowed(&self, used: usize) -> Style {
+        Style {
+            width: (self.style.width).map(|w| w.saturating_sub(self.indent_level * 2 + used)),
+            ..self.style.clone()
+        }
+    }
+
+    /// Lines of a code block, colored by the highlighter when it knows the
+    /// language.
+    fn highlight(&self, language: Option<&str>, code: &str) -> Vec<String> {
+        let tokens = language
+            .zip(self.style.highlighter.as_deref())
+            .and_then(|(language, highlighter)| highlighter.highlight(language, code))
+            .unwrap_or_else(|| vec![Token::new(TokenKind::Plain, code)]);
+
+        let mut lines = vec![String::new()];
+        for token in tokens {
+            let color = self.style.syntax.color(token.kind);
+            let color = self.fg(color.unwrap_or(self.style.code_color));
+            for (i, part) in token.text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(String::new());
+                }
+                if !part.is_empty() {
+                    let line = lines.last_mut().unwrap();
+                    line.push_str(&color);
+                    line.push_str(part);
+                }
+            }
+        }
+        // Match `str::lines`, which drops the line after a final newline
+        if code.ends_with('\n') || code.is_empty() {
+            lines.pop();
+        }
+        lines
+    }
+
+    /// Write a list item after its marker, hanging continuation lines under it.
+    fn write_item(&mut self, marker_width: usize, item: &[Block]) -> fmt::Result {
+        let mut content = String::new();
+        let mut renderer = Renderer::with_style(&mut content, self.narrowed(marker_width));
+        renderer.list_depth = self.list_depth + 1;
+        item.render_with(&mut renderer)?;
+        for (i, line) in content.trim_end_matches('\n').split('\n').enumerate() {
+            if i > 0 && !line.is_empty() {
+                self.write_indent()?;
+                write!(self.writer, "{}", " ".repeat(marker_width))?;
+            }
+            writeln!(self.writer, "{line}")?;
+        }
+        Ok(())
+    }
+
+    /// Render blocks on their own lines, indented by `indent` columns.
+    fn write_indented(&mut self, indent: usize, blocks: &[Block]) -> fmt::Result {
+        let mut rendered = String::new();
+        let style = self.narrowed(indent);
+        blocks.render_with(&mut Renderer::with_style(&mut rendered, style))?;
+        let content = rendered.trim_matches('\n');
+        if content.is_empty() {
+            return Ok(());
+        }
+        for line in content.split('\n') {
+            if !line.is_empty() {
+                self.write_indent()?;
+                write!(self.writer, "{:indent$}", "")?;
+            }
+            writeln!(self.writer, "{line}")?;
+        }
+        Ok(())
+    }
+
+    fn write_image_placeholder(&mut self, alt: &str, url: &str) -> fmt::Result {
+        let icon = if self.style.use_unicode_boxes {
+            "▣ "
         } else {
-            let padding = width - text_len;
-            match align {
-                Alignment::Left => format!("{:width$}", text, width = width),
-                Alignment::Center => {
-                    let left_pad = padding / 2;
-                    let right_pad = padding - left_pad;
-                    format!("{}{}{}", " ".repeat(left_pad), text, " ".repeat(right_pad))
+            "[image] "
+        };
+        let url = clean_url(url);
+        let label = if alt.is_empty() { &url } else { alt };
+        write!(
+            self.writer,
+            "{}{}{}{}{}",
+            self.color(Style::DIM),
+            self.fg(self.style.border_color),
+            icon,
+            self.color(Style::RESET),
+            self.color(Style::ITALIC),
+        )?;
+
+        if self.style.hyperlinks {
+            // The label itself is clickable, so the URL is not repeated
+            write!(self.writer, "\x1b]8;;{url}\x1b\\{label}\x1b]8;;\x1b\\")?;
+            return write!(self.writer, "{}", self.color(Style::RESET));
+        }
+
+        write!(self.writer, "{}{}", label, self.color(Style::RESET))?;
+        if !alt.is_empty() {
+            write!(
+                self.writer,
+                " {}{}({}){}",
+                self.color(Style::DIM),
+                self.fg(self.style.border_color),
+                url,
+                self.color(Style::RESET)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Shrink column widths to fit `max` columns including borders.
+    ///
+    /// Returns `None` when even the narrowest layout would not fit.
+    fn fit_columns(widths: &[usize], max: usize) -> Option<Vec<usize>> {
+        const MIN_COLUMN: usize = 3;
+        let borders = widths.len() * 3 + 1;
+        let natural: usize = widths.iter().sum();
+        if natural + borders <= max {
+            return Some(widths.to_vec());
+        }
+        let available = max.checked_sub(borders)?;
+        if available < widths.len() * MIN_COLUMN {
+            return None;
+        }
+
+        // Columns narrower than an even share keep their width; the rest
+        // split what remains in proportion to their natural width
+        let mut fitted: Vec<Option<usize>> = vec![None; widths.len()];
+        let mut remaining = available;
+        loop {
+            let flexible: Vec<usize> = (0..widths.len()).filter(|&i| fitted[i].is_none()).collect();
+            if flexible.is_empty() {
+                break;
+            }
+            let share = remaining / flexible.len();
+            let narrow: Vec<usize> = flexible
+                .iter()
+                .copied()
+                .filter(|&i| widths[i] <= share)
+                .collect();
+            if narrow.is_empty() {
+                let total: usize = flexible.iter().map(|&i| widths[i]).sum();
+                for i in flexible {
+                    fitted[i] = Some((widths[i] * remaining / total).max(MIN_COLUMN));
                 }
-                Alignment::Right => format!("{:>width$}", text, width = width),
+                break;
+            }
+            for i in narrow {
+                fitted[i] = Some(widths[i]);
+                remaining -= widths[i];
             }
         }
+        let mut fitted: Vec<usize> = fitted.into_iter().flatten().collect();
+
+        // Settle rounding so the columns fill exactly the available width
+        let mut used: usize = fitted.iter().sum();
+        while used > available {
+            let widest = (0..fitted.len()).max_by_key(|&i| fitted[i])?;
+            fitted[widest] -= 1;
+            used -= 1;
+        }
+        while used < available {
+            let Some(i) = (0..fitted.len())
+                .filter(|&i| fitted[i] < widths[i])
+                .max_by_key(|&i| widths[i] - fitted[i])
+            else {
+                break;
+            };
+            fitted[i] += 1;
+            used += 1;
+        }
+        Some(fitted)
     }
 
-    fn measure_inline(inline: &Inline) -> usize {
-        match inline {
-            Inline::Text(t) => t.to_string().chars().count(),
-            Inline::Bold(content) | Inline::Italic(content) | Inline::Strikethrough(content) => {
-                content.iter().map(Self::measure_inline).sum()
+    /// Width of the columns a cell spans, including the borders it covers.
+    fn spanned_width(widths: &[usize], placed: &Placed) -> usize {
+        let columns = &widths[placed.column..placed.column + placed.columns];
+        columns.iter().sum::<usize>() + 3 * (columns.len() - 1)
+    }
+
+    /// Text of each cell in a table grid, at the cell's top-left corner.
+    fn cell_texts(&self, grid: &[Vec<Option<Placed>>]) -> Vec<Vec<String>> {
+        let mut texts: Vec<Vec<String>> = grid
+            .iter()
+            .map(|row| vec![String::new(); row.len()])
+            .collect();
+        for placed in table::origins(grid) {
+            texts[placed.row][placed.column] = placed
+                .cell
+                .content
+                .iter()
+                .map(|inline| self.cell_text(inline))
+                .collect();
+        }
+        texts
+    }
+
+    /// Write a horizontal table border.
+    ///
+    /// `above` and `below` hold, for each boundary between columns, whether
+    /// a vertical line meets the border from that side, or are `None` at the
+    /// top and bottom of the table. `across` holds, for each column, whether
+    /// the border runs through it rather than through a spanning cell.
+    fn write_table_border(
+        &mut self,
+        widths: &[usize],
+        above: Option<&[bool]>,
+        below: Option<&[bool]>,
+        across: &[bool],
+    ) -> fmt::Result {
+        let unicode = self.style.use_unicode_boxes;
+        let h = if unicode { "─" } else { "-" };
+        let (up, down) = (above.is_some(), below.is_some());
+        self.write_indent()?;
+        write!(
+            self.writer,
+            "{}{}{}",
+            self.color(Style::DIM),
+            self.fg(self.style.border_color),
+            Self::junction(unicode, up, down, false, across.first() == Some(&true))
+        )?;
+        for (i, w) in widths.iter().enumerate() {
+            let line = if across[i] { h } else { " " };
+            write!(self.writer, "{}", line.repeat(w + 2))?;
+            if i + 1 < widths.len() {
+                let junction = Self::junction(
+                    unicode,
+                    above.is_some_and(|lines| lines[i]),
+                    below.is_some_and(|lines| lines[i]),
+                    across[i],
+                    across[i + 1],
+                );
+                write!(self.writer, "{junction}")?;
             }
-            Inline::Code(t) => t.to_string().chars().count(),
-            Inline::Link { text, .. } => text.iter().map(Self::measure_inline).sum(),
-            Inline::Image { alt, url } => alt.chars().count() + url.chars().count(),
-            Inline::LineBreak => unreachable!(),
+        }
+        let right = Self::junction(unicode, up, down, across.last() == Some(&true), false);
+        writeln!(self.writer, "{}{}", right, self.color(Style::RESET))
+    }
+
+    /// Box-drawing character joining lines that leave in the given directions.
+    fn junction(unicode: bool, up: bool, down: bool, left: bool, right: bool) -> char {
+        if !unicode {
+            return match (up || down, left || right) {
+                (true, true) => '+',
+                (true, false) => '|',
+                (false, true) => '-',
+                (false, false) => ' ',
+            };
+        }
+        match (up, down, left, right) {
+            (true, true, true, true) => '┼',
+            (false, true, true, true) => '┬',
+            (true, false, true, true) => '┴',
+            (true, true, false, true) => '├',
+            (true, true, true, false) => '┤',
+            (false, true, false, true) => '┌',
+            (false, true, true, false) => '┐',
+            (true, false, false, true) => '└',
+            (true, false, true, false) => '┘',
+            (_, _, false, false) if up || down => '│',
+            (false, false, _, _) if left || right => '─',
+            _ => ' ',
         }
     }
 
-    fn to_plain_string(inline: &Inline) -> String {
+    /// Write the rows of one table section, wrapping cells to the width of
+    /// the columns they span.
+    ///
+    /// Cells spanning several rows flow across them, with borders drawn
+    /// between the rows so the merged cells stand out.
+    fn write_table_section(
+        &mut self,
+        grid: &[Vec<Option<Placed>>],
+        texts: &[Vec<String>],
+        widths: &[usize],
+        alignments: &[Alignment],
+        header: bool,
+    ) -> fmt::Result {
+        let v = if self.style.use_unicode_boxes {
+            "│"
+        } else {
+            "|"
+        };
+        let mut lines: Vec<Vec<Vec<String>>> =
+            grid.iter().map(|row| vec![Vec::new(); row.len()]).collect();
+        for placed in table::origins(grid) {
+            let text = &texts[placed.row][placed.column];
+            let width = Self::spanned_width(widths, &placed);
+            lines[placed.row][placed.column] = if wrap::visible_width(text) > width {
+                wrap::wrap(text, width)
+            } else {
+                vec![text.clone()]
+            };
+        }
+
+        // Rows are as tall as their tallest cell, and the last row under a
+        // cell spanning rows grows when the cell needs more lines
+        let mut heights = vec![1; grid.len()];
+        for placed in table::origins(grid).filter(|p| p.rows == 1) {
+            let height = lines[placed.row][placed.column].len();
+            heights[placed.row] = heights[placed.row].max(height);
+        }
+        for placed in table::origins(grid).filter(|p| p.rows > 1) {
+            let last = placed.row + placed.rows - 1;
+            let available: usize = heights[placed.row..=last].iter().sum();
+            heights[last] += lines[placed.row][placed.column]
+                .len()
+                .saturating_sub(available);
+        }
+        let ruled = table::origins(grid).any(|p| p.rows > 1);
+
+        for (r, row) in grid.iter().enumerate() {
+            if r > 0 && ruled {
+                let above = table::dividers(&grid[r - 1]);
+                let below = table::dividers(row);
+                let across: Vec<bool> = (grid[r - 1].iter().zip(row))
+                    .map(|(&a, &b)| !table::same_cell(a, b))
+                    .collect();
+                self.write_table_border(widths, Some(&above), Some(&below), &across)?;
+            }
+            for line in 0..heights[r] {
+                self.write_indent()?;
+                write!(
+                    self.writer,
+                    "{}{}{}{}",
+                    self.color(Style::DIM),
+                    self.fg(self.style.border_color),
+                    v,
+                    self.color(Style::RESET)
+                )?;
+                let mut c = 0;
+                while c < widths.len() {
+                    let (text, width, column, span) = match row[c] {
+                        Some(placed) => {
+                            let offset = heights[placed.row..r].iter().sum::<usize>() + line;
+                            let text = lines[placed.row][placed.column].get(offset);
+                            let width = Self::spanned_width(widths, &placed);
+                            (text, width, placed.column, placed.columns)
+                        }
+                        None => (None, widths[c], c, 1),
+                    };
+                    write!(self.writer, " ")?;
+                    if header {
+                        write!(
+                            self.writer,
+                            "{}{}",
+                            self.color(Style::BOLD),
+                            self.fg(self.style.table_header_color)
+                        )?;
+                    }
+                    let text = text.map_or("", String::as_str);
+                    let align = alignments.get(column).copied().unwrap_or(Alignment::Left);
+                    let padded = width::pad_measured(text, wrap::visible_width(text), width, align);
+                    write!(self.writer, "{padded}")?;
+                    write!(
+                        self.writer,
+                        "{} {}{}{}{}",
+                        self.color(if header { Style::RESET } else { "" }),
+                        self.color(Style::DIM),
+                        self.fg(self.style.border_color),
+                        v,
+                        self.color(Style::RESET)
+                    )?;
+                    c += span;
+                }
+                writeln!(self.writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records for body rows, with a value for every cell starting in a
+    /// column. Cells under one spanning header share a single label.
+    fn body_records<'t>(
+        header: &[Option<Placed>],
+        labels: &'t [String],
+        grid: &[Vec<Option<Placed>>],
+        texts: &[Vec<String>],
+    ) -> Vec<Vec<(&'t str, String)>> {
+        grid.iter()
+            .map(|row| {
+                let mut record: Vec<(Option<usize>, &str, Vec<&str>)> = Vec::new();
+                for (c, slot) in row.iter().enumerate() {
+                    let text = match slot {
+                        Some(p) if p.column != c => continue,
+                        Some(p) => texts[p.row][c].as_str(),
+                        None => "",
+                    };
+                    let group = header.get(c).copied().flatten().map(|h| h.column);
+                    match record.last_mut() {
+                        Some((last, _, values)) if group.is_some() && *last == group => {
+                            values.push(text)
+                        }
+                        _ => {
+                            let label = group.map_or("", |h| labels[h].as_str());
+                            record.push((group, label, vec![text]));
+                        }
+                    }
+                }
+                record
+                    .into_iter()
+                    .map(|(_, label, values)| (label, Self::join_values(&values)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Records for footer rows, labeled by their first cell rather than by
+    /// the headers above, which footer cells often span.
+    fn footer_records<'t>(
+        grid: &[Vec<Option<Placed>>],
+        texts: &'t [Vec<String>],
+    ) -> Vec<Vec<(&'t str, String)>> {
+        grid.iter()
+            .enumerate()
+            .map(|(r, row)| {
+                let mut cells = (row.iter().enumerate())
+                    .filter_map(|(c, slot)| slot.filter(|p| p.starts_at(r, c)))
+                    .map(|p| texts[r][p.column].as_str());
+                let label = cells.next().unwrap_or("");
+                vec![(label, Self::join_values(&cells.collect::<Vec<_>>()))]
+            })
+            .collect()
+    }
+
+    /// Values of the cells in one record entry, skipping empty ones.
+    fn join_values(values: &[&str]) -> String {
+        let values: Vec<&str> = values.iter().copied().filter(|v| !v.is_empty()).collect();
+        values.join(" / ")
+    }
+
+    /// Write each row as a block of `header: value` lines for narrow widths,
+    /// with footer rows below a rule.
+    fn write_records(
+        &mut self,
+        body: &[Vec<(&str, String)>],
+        footer: &[Vec<(&str, String)>],
+    ) -> fmt::Result {
+        let records = || body.iter().chain(footer);
+        let max = self
+            .style
+            .width
+            .unwrap_or(usize::MAX)
+            .saturating_sub(self.indent_level * 2);
+        let label_width = records()
+            .flatten()
+            .map(|(header, _)| wrap::visible_width(header))
+            .max()
+            .unwrap_or(0);
+        // Labels are only padded into a column when that leaves room for values
+        let label_width = if label_width + 2 < max / 2 {
+            label_width
+        } else {
+            0
+        };
+        let hang = if label_width > 0 { label_width + 2 } else { 2 };
+
+        for (r, record) in records().enumerate() {
+            if r > 0 {
+                writeln!(self.writer)?;
+            }
+            if r == body.len() {
+                let width = records()
+                    .flatten()
+                    .map(|(_, value)| hang + wrap::visible_width(value))
+                    .max()
+                    .unwrap_or(hang)
+                    .min(max);
+                let rule = if self.style.use_unicode_boxes {
+                    "─"
+                } else {
+                    "-"
+                };
+                self.write_indent()?;
+                writeln!(
+                    self.writer,
+                    "{}{}{}{}",
+                    self.color(Style::DIM),
+                    self.fg(self.style.border_color),
+                    rule.repeat(width),
+                    self.color(Style::RESET)
+                )?;
+            }
+            for (header, value) in record {
+                self.write_indent()?;
+                write!(
+                    self.writer,
+                    "{}{}{}:{}",
+                    self.color(Style::BOLD),
+                    self.fg(self.style.table_header_color),
+                    header,
+                    self.color(Style::RESET)
+                )?;
+
+                let first = label_width.saturating_sub(wrap::visible_width(header)) + 1;
+                let inline = label_width > 0;
+                let lines = wrap::wrap(value, max.saturating_sub(hang).max(1));
+                for (n, line) in lines.iter().enumerate() {
+                    if n == 0 && inline {
+                        write!(self.writer, "{}", " ".repeat(first))?;
+                    } else {
+                        writeln!(self.writer)?;
+                        self.write_indent()?;
+                        write!(self.writer, "{}", " ".repeat(hang))?;
+                    }
+                    write!(self.writer, "{line}")?;
+                }
+                writeln!(self.writer)?;
+            }
+        }
+        writeln!(self.writer)
+    }
+
+    /// Unstyled text of a table cell, keeping links clickable when
+    /// hyperlinks are enabled.
+    fn cell_text(&self, inline: &Inline) -> String {
         match inline {
             Inline::Text(t) => t.to_string(),
             Inline::Bold(content) | Inline::Italic(content) | Inline::Strikethrough(content) => {
-                content.iter().map(Self::to_plain_string).collect()
+                content.iter().map(|i| self.cell_text(i)).collect()
             }
             Inline::Code(t) => t.to_string(),
-            Inline::Link { text, .. } => text.iter().map(Self::to_plain_string).collect(),
-            Inline::Image { alt: _, url: _ } => unimplemented!(),
-            Inline::LineBreak => unreachable!(),
+            Inline::Link { text, url } => {
+                let text: String = text.iter().map(|i| self.cell_text(i)).collect();
+                if self.style.hyperlinks && !url.starts_with('#') {
+                    format!("\x1b]8;;{}\x1b\\{text}\x1b]8;;\x1b\\", clean_url(url))
+                } else {
+                    text
+                }
+            }
+            Inline::Image { alt, .. } => alt.to_string(),
+            Inline::LineBreak => " ".to_string(),
+            Inline::FootnoteRef(label) => self.footnote_marker(label),
+        }
+    }
+
+    /// Superscript digits for a numbered footnote, or the label in brackets.
+    fn footnote_marker(&self, label: &str) -> String {
+        const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+        let numbered = !label.is_empty() && label.bytes().all(|b| b.is_ascii_digit());
+        if self.style.use_unicode_boxes && numbered {
+            label
+                .bytes()
+                .map(|b| SUPERSCRIPTS[(b - b'0') as usize])
+                .collect()
+        } else {
+            format!("[{label}]")
         }
     }
+
+    /// Write footnote definitions in a "Notes" section after the document.
+    fn write_notes(&mut self, notes: &[Block]) -> fmt::Result {
+        let rule = if self.style.use_unicode_boxes {
+            "─"
+        } else {
+            "-"
+        };
+        writeln!(self.writer)?;
+        writeln!(
+            self.writer,
+            "{}{}{}{}",
+            self.color(Style::DIM),
+            self.fg(self.style.border_color),
+            rule.repeat(20),
+            self.color(Style::RESET)
+        )?;
+        writeln!(
+            self.writer,
+            "{}Notes{}",
+            self.color(Style::BOLD),
+            self.color(Style::RESET)
+        )?;
+        notes.render_with(self)?;
+        writeln!(self.writer)
+    }
+}
+
+/// `url` with control characters percent-encoded, so a hostile document
+/// cannot end an OSC 8 hyperlink early or start escape sequences of its own.
+fn clean_url(url: &str) -> Cow<'_, str> {
+    if !url.contains(char::is_control) {
+        return Cow::Borrowed(url);
+    }
+    let mut clean = String::with_capacity(url.len());
+    for c in url.chars() {
+        if c.is_control() {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                clean.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            clean.push(c);
+        }
+    }
+    Cow::Owned(clean)
 }
 
 #[cfg(test)]
@@ -818,6 +1716,21 @@ mod tests {
         assert!(terminal_output.contains("\x1b[9m")); // strikethrough
     }
 
+    #[test]
+    fn test_terminal_heading_levels() {
+        // Levels outside 1 to 6 are clamped rather than panicking
+        let heading = |level| Block::Heading {
+            level,
+            content: vec![text("Title")],
+            id: None,
+        };
+        let style = Style::ascii().colors(false);
+        let output = Renderer::to_string_with_style(&heading(0), style.clone());
+        assert!(output.starts_with("\n# Title\n"));
+        let output = Renderer::to_string_with_style(&heading(9), style);
+        assert!(output.starts_with("\n###### Title\n"));
+    }
+
     #[test]
     fn test_terminal_table_alignment() {
         // Create a table with different alignments
@@ -857,6 +1770,411 @@ mod tests {
         assert!(output.contains("Here"));
     }
 
+    #[test]
+    fn test_terminal_table_wide_characters() {
+        let table = table(
+            (Align::left("Word"), Align::right("Note")),
+            (("日本語", "cjk"), ("👩‍💻", "zwj"), ("e\u{301}", "combining")),
+        );
+        let output = Renderer::to_string_with_style(&table, Style::ascii().colors(false));
+        assert_eq!(
+            output,
+            "+--------+-----------+\n\
+             | Word   |      Note |\n\
+             +--------+-----------+\n\
+             | 日本語 |       cjk |\n\
+             | 👩‍💻     |       zwj |\n\
+             | e\u{301}      | combining |\n\
+             +--------+-----------+\n\n"
+        );
+    }
+
+    #[test]
+    fn test_terminal_table_spans() {
+        let table = table(
+            (
+                Align::left("Region"),
+                Align::right(cell("Sales").with_colspan(2)),
+            ),
+            [
+                vec![cell("North").with_rowspan(2), cell("Q1"), cell("10")],
+                vec![cell("Q2"), cell("12")],
+                vec![cell("South"), cell("Both halves").with_colspan(2)],
+            ],
+        )
+        .with_footer([(cell("Total").with_colspan(2), "22")])
+        .with_caption("Sales by region");
+        let output = Renderer::to_string_with_style(&table, Style::plain());
+        assert_eq!(
+            output,
+            "Sales by region\n\
+             ┌────────┬─────────────┐\n\
+             │ Region │       Sales │\n\
+             ├────────┼─────┬───────┤\n\
+             │ North  │  Q1 │    10 │\n\
+             │        ├─────┼───────┤\n\
+             │        │  Q2 │    12 │\n\
+             ├────────┼─────┴───────┤\n\
+             │ South  │ Both halves │\n\
+             ├────────┴─────┬───────┤\n\
+             │ Total        │    22 │\n\
+             └──────────────┴───────┘\n\n"
+        );
+
+        // ASCII borders keep their corners where merged cells meet
+        let output = Renderer::to_string_with_style(&table, Style::ascii().colors(false));
+        assert!(output.contains("|        +-----+-------+\n"));
+        assert!(output.contains("+--------+-------------+\n"));
+
+        // Records label spanned columns once and set footer rows apart
+        let output = Renderer::to_string_with_style(&table, Style::plain().width(16));
+        assert_eq!(
+            output,
+            "Sales by region\n\
+             Region:\n  North\nSales:\n  Q1 / 10\n\n\
+             Region:\n  North\nSales:\n  Q2 / 12\n\n\
+             Region:\n  South\nSales:\n  Both halves\n\n\
+             ─────────────\nTotal:\n  22\n\n"
+        );
+    }
+
+    #[test]
+    fn test_terminal_nested_list() {
+        let list = ul((
+            Block::BlockList(vec![
+                p("Parent"),
+                ul((
+                    "child",
+                    Block::BlockList(vec![p("deeper"), ol(["one", "two"])]),
+                )),
+            ]),
+            Block::BlockList(vec![p("First."), p("Second.")]),
+        ));
+        let output = Renderer::to_string_with_style(&list, Style::plain());
+        assert_eq!(
+            output,
+            "• Parent\n  ◦ child\n  ◦ deeper\n    1. one\n    2. two\n• First.\n  Second.\n\n"
+        );
+
+        // Ordered markers are right-aligned once numbers reach two digits
+        let items: Vec<Block> = (1..=10).map(|i| p(i.to_string())).collect();
+        let output = Renderer::to_string_with_style(&ol(items), Style::ascii().colors(false));
+        assert!(output.starts_with(" 1. 1\n"));
+        assert!(output.contains("\n10. 10\n"));
+    }
+
+    #[test]
+    fn test_terminal_toc() {
+        let output = doc([h1("Guide"), h2("Install"), h3("From source"), h2("Usage")])
+            .with_style(Style::plain())
+            .with_toc(crate::Toc::new().levels(2, 3))
+            .to_string();
+        assert!(output.starts_with("• Install\n  ◦ From source\n• Usage\n\n"));
+        assert!(!output.contains('#'));
+
+        // Numbered sections carry their numbers into the outline
+        let output = doc([h2("Install"), h3("From source")])
+            .with_style(Style::plain())
+            .with_numbering(crate::Numbering::new())
+            .with_toc(crate::TocPosition::Start)
+            .to_string();
+        assert!(output.starts_with("• 1. Install\n  ◦ 1.1 From source\n"));
+        assert!(output.contains("1.1 From source\n\n"));
+
+        // Cross-references only show their text
+        let output =
+            Renderer::to_string_with_style(&p(vec![xref("See usage", "usage")]), Style::plain());
+        assert_eq!(output, "See usage\n");
+    }
+
+    #[test]
+    fn test_terminal_colors() {
+        let brand = Color::Rgb(255, 135, 0);
+        let style = Style::default().link_color(brand);
+        let link = p(vec![link("Docs", "#docs")]);
+
+        let output = Renderer::to_string_with_style(&link, style.clone());
+        assert!(output.contains("\x1b[38;2;255;135;0mDocs"));
+        let output =
+            Renderer::to_string_with_style(&link, style.clone().color_depth(ColorDepth::Ansi256));
+        assert!(output.contains("\x1b[38;5;208mDocs"));
+        let output = Renderer::to_string_with_style(&link, style.color_depth(ColorDepth::Ansi16));
+        assert!(output.contains("\x1b[33mDocs"));
+    }
+
+    #[test]
+    fn test_terminal_highlighting() {
+        let code = code_block("rust", "let n = 1; // one\nfn f() {}\n");
+        let style = Style::ascii().theme(&Theme::dark());
+        let output = Renderer::to_string_with_style(&code, style.clone());
+        let keyword = Color::BrightMagenta.fg();
+        let plain = Color::Green.fg();
+        assert!(output.contains(&format!(
+            "{keyword}let{plain} n = {}1{plain}; {}// one\n",
+            Color::BrightCyan.fg(),
+            Color::BrightBlack.fg()
+        )));
+        assert!(output.contains(&format!("{keyword}fn{plain} f() {{}}\n")));
+
+        // Unknown languages and disabled highlighting use the code color
+        let output = Renderer::to_string_with_style(&code, style.clone().highlighter(None));
+        assert!(output.contains(&format!("{plain}let n = 1; // one\n")));
+
+        // Highlighters built at runtime need no 'static borrow
+        struct Whole(String);
+        impl Highlighter for Whole {
+            fn highlight<'a>(&self, language: &str, code: &'a str) -> Option<Vec<Token<'a>>> {
+                (language == self.0).then(|| vec![Token::new(TokenKind::Meta, code)])
+            }
+        }
+        let whole = Arc::new(Whole(String::from("rust")));
+        let meta = style.syntax.meta.fg();
+        let output = Renderer::to_string_with_style(&code, style.highlighter(Some(whole)));
+        assert!(output.contains(&format!("{meta}let n = 1; // one\n")));
+        let plain_text = Renderer::to_string_with_style(&code, Style::ascii().colors(false));
+        assert!(plain_text.contains("| let n = 1; // one\n| fn f() {}\n+--"));
+    }
+
+    #[test]
+    fn test_terminal_code_gutter() {
+        let snippet = code_block("rust", "let a = 1;\nlet b = a / 0;\nok()")
+            .with_title("main.rs")
+            .with_start_line(9)
+            .with_highlighted([10]);
+        let output = Renderer::to_string_with_style(&snippet, Style::plain());
+        assert_eq!(
+            output,
+            "\n┌─[ rust ] main.rs\n│  9 │ let a = 1;\n│ 10 ▶ let b = a / 0;\n│ 11 │ ok()\n└─────\n\n"
+        );
+
+        // Highlighted lines can be marked without numbers
+        let snippet = code_block((), "a\nb").with_highlighted([2]);
+        let output = Renderer::to_string_with_style(&snippet, Style::ascii().colors(false));
+        assert_eq!(output, "\n+--────\n|   a\n| > b\n+--────\n\n");
+    }
+
+    #[test]
+    fn test_terminal_hyperlinks() {
+        let para = p(vec![text("See "), link("the docs", "https://docs.rs")]);
+        let output = Renderer::to_string_with_style(&para, Style::plain());
+        assert_eq!(output, "See the docs (https://docs.rs)\n");
+
+        let style = Style::plain().hyperlinks(true);
+        let output = Renderer::to_string_with_style(&para, style);
+        assert_eq!(
+            output,
+            "See \x1b]8;;https://docs.rs\x1b\\the docs\x1b]8;;\x1b\\\n"
+        );
+
+        // Escapes take no columns when aligning table cells
+        let links = table(
+            ("Crate", "Docs"),
+            [("docloom", link("docs", "https://docs.rs/docloom"))],
+        );
+        let output =
+            Renderer::to_string_with_style(&links, Style::ascii().colors(false).hyperlinks(true));
+        assert!(
+            output
+                .contains("| docloom | \x1b]8;;https://docs.rs/docloom\x1b\\docs\x1b]8;;\x1b\\ |")
+        );
+        assert!(output.contains("| Crate   | Docs |"));
+
+        // Control characters in URLs cannot smuggle in escape sequences
+        let hostile = "https://x\x1b]0;pwned\x07\u{9b}";
+        let clean = "https://x%1B]0;pwned%07%C2%9B";
+        let style = Style::plain().hyperlinks(true);
+        let output = Renderer::to_string_with_style(&p(vec![link("click", hostile)]), style);
+        assert_eq!(
+            output,
+            format!("\x1b]8;;{clean}\x1b\\click\x1b]8;;\x1b\\\n")
+        );
+        let output =
+            Renderer::to_string_with_style(&p(vec![link("click", hostile)]), Style::plain());
+        assert_eq!(output, format!("click ({clean})\n"));
+        let image = p(vec![Inline::Image {
+            alt: String::new(),
+            url: hostile.into(),
+        }]);
+        let output = Renderer::to_string_with_style(&image, Style::plain().hyperlinks(true));
+        assert_eq!(
+            output,
+            format!("▣ \x1b]8;;{clean}\x1b\\{clean}\x1b]8;;\x1b\\\n")
+        );
+        let cell = table(("Docs",), [(link("docs", hostile),)]);
+        let output =
+            Renderer::to_string_with_style(&cell, Style::ascii().colors(false).hyperlinks(true));
+        assert!(output.contains(&format!("\x1b]8;;{clean}\x1b\\docs\x1b]8;;\x1b\\")));
+        assert!(!output.contains('\x07'));
+    }
+
+    #[test]
+    fn test_terminal_callouts() {
+        let callout = warning((p("Disk almost full."), ul(["Free space"])));
+        let output = Renderer::to_string_with_style(&callout, Style::plain().width(16));
+        assert_eq!(
+            output,
+            "\n┌─ Warning\n│ Disk almost\n│ full.\n│ • Free space\n└─────\n\n"
+        );
+
+        let output = Renderer::to_string_with_style(
+            &note(p("Text")).with_title("Custom"),
+            Style::plain().unicode_boxes(false),
+        );
+        assert_eq!(output, "\n+-- Custom\n| Text\n+--────\n\n");
+
+        // Borders and titles take the kind's color
+        let output = Renderer::to_string(&danger(p("Text")));
+        assert!(output.starts_with("\n\x1b[91m┌─ \x1b[1mDanger\x1b[0m\n\x1b[91m│\x1b[0m Text"));
+    }
+
+    #[test]
+    fn test_terminal_definition_lists() {
+        let list = dl((
+            (code("--quiet"), "Suppress all output."),
+            ("Config", block((p("Read from disk."), ul(["TOML"])))),
+        ));
+        let output = Renderer::to_string_with_style(&list, Style::plain().width(16));
+        assert_eq!(
+            output,
+            "--quiet\n    Suppress all\n    output.\n\nConfig\n    Read from\n    disk.\n    • TOML\n\n"
+        );
+
+        let output = Renderer::to_string(&dl([("Key", "Value")]));
+        assert!(output.starts_with("\x1b[1mKey\x1b[0m\n    Value"));
+    }
+
+    #[test]
+    fn test_terminal_details() {
+        let section = details("CI log", (p("Compiling the crate"), ul(["ok"])));
+        let output = Renderer::to_string_with_style(&section, Style::plain());
+        assert_eq!(output, "▸ CI log\n\n");
+
+        let output = Renderer::to_string_with_style(
+            &section.clone().with_open(true),
+            Style::plain().width(14),
+        );
+        assert_eq!(output, "▾ CI log\n  Compiling\n  the crate\n  • ok\n\n");
+
+        let output = Renderer::to_string_with_style(&section, Style::plain().unicode_boxes(false));
+        assert_eq!(output, "> CI log\n\n");
+    }
+
+    #[test]
+    fn test_terminal_footnotes() {
+        let (cite, note) = footnote("Long source text");
+        let blocks = [p(("Claim", cite.clone(), ".")), note, p(("Again", cite))];
+        let output = doc(blocks.clone())
+            .with_style(Style::plain().width(14))
+            .to_string();
+        assert_eq!(
+            output,
+            "Claim¹.\nAgain¹\n\n────────────────────\nNotes\n¹ Long source\n  text\n\n"
+        );
+
+        let output = doc(blocks)
+            .with_style(Style::plain().unicode_boxes(false))
+            .to_string();
+        assert!(output.starts_with("Claim[1].\n"));
+        assert!(output.ends_with("------\nNotes\n[1] Long source text\n\n"));
+    }
+
+    #[test]
+    fn test_terminal_image() {
+        let style = Style::plain();
+        let image = Block::Image {
+            alt: "Logo".into(),
+            url: "logo.png".into(),
+        };
+        let output = Renderer::to_string_with_style(&image, style.clone());
+        assert_eq!(output.trim(), "▣ Logo (logo.png)");
+
+        let output = Renderer::to_string_with_style(&image, Style::ascii().colors(false));
+        assert_eq!(output.trim(), "[image] Logo (logo.png)");
+
+        // Hyperlinks make the label clickable instead of printing the URL
+        let output = Renderer::to_string_with_style(&image, style.clone().hyperlinks(true));
+        assert_eq!(output.trim(), "▣ \x1b]8;;logo.png\x1b\\Logo\x1b]8;;\x1b\\");
+
+        // Inline images and table cells no longer panic
+        let inline = Inline::Image {
+            alt: String::new(),
+            url: "icon.png".into(),
+        };
+        let output =
+            Renderer::to_string_with_style(&p(vec![text("See "), inline.clone()]), style.clone());
+        assert_eq!(output.trim(), "See ▣ icon.png");
+        let output = Renderer::to_string_with_style(&table(("Icon",), [(inline,)]), style.clone());
+        assert!(output.contains("Icon"));
+
+        // Unsupported sources fall back to the placeholder in graphics modes
+        let output = Renderer::to_string_with_style(&image, style.image_mode(ImageMode::HalfBlock));
+        assert_eq!(output.trim(), "▣ Logo (logo.png)");
+    }
+
+    #[test]
+    fn test_terminal_wrapping() {
+        let style = Style::plain().width(20);
+        let para = p("The quick brown fox jumps over the lazy dog.");
+        let output = Renderer::to_string_with_style(&para, style.clone());
+        assert_eq!(output, "The quick brown fox\njumps over the lazy\ndog.\n");
+
+        // List items hang continuation lines under the marker
+        let list = ul(["Wrapped list items keep their hanging indent."]);
+        let output = Renderer::to_string_with_style(&list, style.clone());
+        assert_eq!(
+            output,
+            "• Wrapped list items\n  keep their hanging\n  indent.\n\n"
+        );
+
+        // Quote borders are accounted for in the available width
+        let bq = quote(p("Quoted text wraps inside the border."));
+        let output = Renderer::to_string_with_style(&bq, style.unicode_boxes(false));
+        assert_eq!(output, "| Quoted text wraps\n| inside the border.\n\n");
+
+        // Colors are closed before each break and reopened after it
+        let para = p((bold("bold text that wraps"), " plain"));
+        let output = Renderer::to_string_with_style(&para, Style::default().width(10));
+        assert!(output.starts_with("\x1b[1mbold text\x1b[0m\x1b[0m\n\x1b[1mthat wraps"));
+    }
+
+    #[test]
+    fn test_terminal_table_fitting() {
+        let table = table(
+            ("Name", "Description"),
+            [("docloom", "Compose documents and render them anywhere")],
+        );
+
+        // Wide enough: unchanged natural layout
+        let style = Style::ascii().colors(false);
+        let natural = Renderer::to_string_with_style(&table, style.clone());
+        assert_eq!(
+            natural,
+            Renderer::to_string_with_style(&table, style.clone().width(80))
+        );
+
+        // Narrow: columns shrink and cells wrap inside the borders
+        let output = Renderer::to_string_with_style(&table, style.clone().width(30));
+        assert!(output.lines().all(|line| line.chars().count() <= 30));
+        assert_eq!(
+            output,
+            "+---------+------------------+\n\
+             | Name    | Description      |\n\
+             +---------+------------------+\n\
+             | docloom | Compose          |\n\
+             |         | documents and    |\n\
+             |         | render them      |\n\
+             |         | anywhere         |\n\
+             +---------+------------------+\n\n"
+        );
+
+        // Too narrow for any grid: one record per row
+        let output = Renderer::to_string_with_style(&table, style.width(12));
+        assert_eq!(
+            output,
+            "Name:\n  docloom\nDescription:\n  Compose\n  documents\n  and render\n  them\n  anywhere\n\n"
+        );
+    }
+
     #[test]
     fn test_terminal_blockquote() {
         // Simple blockquote test with ASCII style and no colors
@@ -883,5 +2201,13 @@ mod tests {
         println!("Complex terminal blockquote output:\n{}", terminal_output);
         assert!(terminal_output.contains("| ### Header in Quote"));
         assert!(terminal_output.contains("| Content in quote."));
+
+        // Themes can replace the border character
+        let style = Style::plain().theme(&Theme {
+            quote_border: Some('┃'),
+            ..Theme::monochrome()
+        });
+        let terminal_output = Renderer::to_string_with_style(&bq, style);
+        assert!(terminal_output.contains("┃ Content in quote."));
     }
 }
@@ -0,0 +1,333 @@
+//! Terminal colors and downgrading them to what a terminal supports.
+
+use std::{fmt, str::FromStr};
+
+/// A foreground or background color for terminal output.
+///
+/// Named colors use the terminal's own palette, while [`Color::Ansi256`] and
+/// [`Color::Rgb`] are converted to the closest supported color when rendering
+/// at a lower [`ColorDepth`].
+///
+/// # Examples
+/// ```rust
+/// use docloom::term::{Color, ColorDepth};
+///
+/// let brand = Color::Rgb(255, 135, 0);
+/// assert_eq!(brand.fg(), "\x1b[38;2;255;135;0m");
+/// assert_eq!(brand.downgrade(ColorDepth::Ansi256), Color::Ansi256(208));
+/// assert_eq!(brand.downgrade(ColorDepth::Ansi16), Color::Yellow);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// The terminal's own foreground or background color.
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// An index into the 256-color palette.
+    Ansi256(u8),
+    /// A 24-bit truecolor value.
+    Rgb(u8, u8, u8),
+}
+
+/// How many colors a terminal can display.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorDepth {
+    /// The 16 named colors.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    #[default]
+    TrueColor,
+}
+
+/// Named colors in palette order.
+const NAMED: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+/// Typical RGB values of the named colors, as used by xterm.
+const NAMED_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Names accepted by [`Color::from_str`], in the order of [`NAMED`].
+const NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// Channel levels of the 6x6x6 color cube in the 256-color palette.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    /// ANSI escape code that sets this color as the foreground.
+    pub fn fg(self) -> String {
+        self.escape(false)
+    }
+
+    /// ANSI escape code that sets this color as the background.
+    pub fn bg(self) -> String {
+        self.escape(true)
+    }
+
+    /// The closest color that a terminal with `depth` can display.
+    pub fn downgrade(self, depth: ColorDepth) -> Color {
+        match (self, depth) {
+            (_, ColorDepth::TrueColor) => self,
+            (Color::Rgb(r, g, b), ColorDepth::Ansi256) => Color::Ansi256(to_ansi256(r, g, b)),
+            (Color::Ansi256(index), ColorDepth::Ansi16) if index < 16 => NAMED[index as usize],
+            (Color::Ansi256(index), ColorDepth::Ansi16) => {
+                let (r, g, b) = ansi256_rgb(index);
+                nearest_named(r, g, b)
+            }
+            (Color::Rgb(r, g, b), ColorDepth::Ansi16) => nearest_named(r, g, b),
+            _ => self,
+        }
+    }
+
+    fn escape(self, background: bool) -> String {
+        let (base, extended) = if background { (40, 48) } else { (30, 38) };
+        match self {
+            Color::Default => format!("\x1b[{}m", base + 9),
+            Color::Ansi256(index) => format!("\x1b[{extended};5;{index}m"),
+            Color::Rgb(r, g, b) => format!("\x1b[{extended};2;{r};{g};{b}m"),
+            named => {
+                let index = NAMED.iter().position(|c| *c == named).unwrap_or(0);
+                let code = if index < 8 {
+                    base + index
+                } else {
+                    base + 60 + index - 8
+                };
+                format!("\x1b[{code}m")
+            }
+        }
+    }
+}
+
+/// Error returned when parsing a [`Color`] fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseColorError(String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid color `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parse a color name such as `bright-cyan`, a 256-color palette index
+    /// such as `208`, or a hex RGB value such as `#ff8700` or `#f80`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseColorError(s.to_string());
+        let name = s.trim().to_ascii_lowercase().replace(['_', ' '], "-");
+
+        if let Some(hex) = name.strip_prefix('#') {
+            let digits: Vec<u8> = hex
+                .chars()
+                .map(|c| c.to_digit(16).map(|d| d as u8))
+                .collect::<Option<_>>()
+                .ok_or_else(error)?;
+            return match digits[..] {
+                [r, g, b] => Ok(Color::Rgb(r * 17, g * 17, b * 17)),
+                [r1, r2, g1, g2, b1, b2] => {
+                    Ok(Color::Rgb(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2))
+                }
+                _ => Err(error()),
+            };
+        }
+        if let Ok(index) = name.parse() {
+            return Ok(Color::Ansi256(index));
+        }
+        if name == "default" {
+            return Ok(Color::Default);
+        }
+        let name = match name.strip_prefix("bright") {
+            Some(rest) if !rest.starts_with('-') => format!("bright-{rest}"),
+            _ => name,
+        };
+        NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|i| NAMED[i])
+            .ok_or_else(error)
+    }
+}
+
+/// Closest 256-color palette index for an RGB value.
+fn to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| match v {
+        0..48 => 0,
+        48..115 => 1,
+        v => (v - 35) / 40,
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+
+    // Grays are often closer to the 24-step grayscale ramp than the cube
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = match average {
+        0..8 => 16,
+        239.. => 231,
+        average => 232 + ((average - 8) / 10).min(23) as u8,
+    };
+    let distance = |index: u8| {
+        let (cr, cg, cb) = ansi256_rgb(index);
+        distance((r, g, b), (cr, cg, cb))
+    };
+    if distance(gray) < distance(cube) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// RGB value of a 256-color palette index.
+fn ansi256_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..16 => NAMED_RGB[index as usize],
+        16..232 => {
+            let i = index - 16;
+            (
+                CUBE[(i / 36) as usize],
+                CUBE[(i / 6 % 6) as usize],
+                CUBE[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let v = 8 + (index - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+fn nearest_named(r: u8, g: u8, b: u8) -> Color {
+    let nearest = (0..16)
+        .min_by_key(|&i| distance((r, g, b), NAMED_RGB[i]))
+        .unwrap_or(0);
+    NAMED[nearest]
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(Color::Green.fg(), "\x1b[32m");
+        assert_eq!(Color::BrightBlack.fg(), "\x1b[90m");
+        assert_eq!(Color::Black.bg(), "\x1b[40m");
+        assert_eq!(Color::BrightWhite.bg(), "\x1b[107m");
+        assert_eq!(Color::Ansi256(208).fg(), "\x1b[38;5;208m");
+        assert_eq!(Color::Rgb(1, 2, 3).bg(), "\x1b[48;2;1;2;3m");
+        assert_eq!(Color::Default.fg(), "\x1b[39m");
+    }
+
+    #[test]
+    fn test_downgrade() {
+        use ColorDepth::*;
+
+        assert_eq!(
+            Color::Rgb(1, 2, 3).downgrade(TrueColor),
+            Color::Rgb(1, 2, 3)
+        );
+        assert_eq!(Color::Rgb(0, 0, 0).downgrade(Ansi256), Color::Ansi256(16));
+        assert_eq!(
+            Color::Rgb(95, 135, 255).downgrade(Ansi256),
+            Color::Ansi256(69)
+        );
+        assert_eq!(
+            Color::Rgb(128, 128, 128).downgrade(Ansi256),
+            Color::Ansi256(244)
+        );
+        assert_eq!(Color::Rgb(0, 200, 0).downgrade(Ansi16), Color::Green);
+        assert_eq!(Color::Ansi256(9).downgrade(Ansi16), Color::BrightRed);
+        assert_eq!(Color::Ansi256(231).downgrade(Ansi16), Color::BrightWhite);
+        // Named colors are left to the terminal palette
+        assert_eq!(Color::Cyan.downgrade(Ansi16), Color::Cyan);
+        assert_eq!(Color::Default.downgrade(Ansi16), Color::Default);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("cyan".parse(), Ok(Color::Cyan));
+        assert_eq!("Bright_Cyan".parse(), Ok(Color::BrightCyan));
+        assert_eq!("brightblack".parse(), Ok(Color::BrightBlack));
+        assert_eq!("default".parse(), Ok(Color::Default));
+        assert_eq!("208".parse(), Ok(Color::Ansi256(208)));
+        assert_eq!("#ff8700".parse(), Ok(Color::Rgb(255, 135, 0)));
+        assert_eq!("#f80".parse(), Ok(Color::Rgb(255, 136, 0)));
+        assert!("#ff87".parse::<Color>().is_err());
+        assert!("256".parse::<Color>().is_err());
+        assert_eq!(
+            "mauve".parse::<Color>().unwrap_err().to_string(),
+            "invalid color `mauve`"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Terminal capability detection from the environment.
+//!
+//! Colors follow the `NO_COLOR` and `CLICOLOR` conventions, and Unicode box
+//! drawing follows the locale, so output piped into files or CI logs stays
+//! free of escape codes without every application repeating these checks.
+
+use std::io::IsTerminal;
+
+use super::{ColorDepth, Style, terminal_width};
+
+impl Style {
+    /// Create a style suited to stdout and the current environment.
+    ///
+    /// Colors are disabled when `NO_COLOR` is set, when `CLICOLOR=0`, when
+    /// `TERM=dumb` or when stdout is not a terminal; `CLICOLOR_FORCE` enables
+    /// them regardless of the terminal. RGB colors are downgraded unless
+    /// `COLORTERM` announces truecolor, and to 16 colors unless `TERM` names
+    /// a 256-color terminal. Unicode boxes are used when the locale
+    /// (`LC_ALL`, `LC_CTYPE` or `LANG`) is UTF-8, and OSC 8 hyperlinks in
+    /// terminals known to support them.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use docloom::term::{Style, doc};
+    /// use docloom::prelude::*;
+    ///
+    /// println!("{}", doc(p("Hello")).with_style(Style::detect()));
+    /// ```
+    pub fn detect() -> Self {
+        detect_with(
+            |name| std::env::var(name).ok(),
+            std::io::stdout().is_terminal(),
+        )
+    }
+
+    /// Like [`Style::detect`], and also wrap text to [`terminal_width`].
+    pub fn auto() -> Self {
+        Self::detect().width(terminal_width())
+    }
+}
+
+/// Pick colors and box drawing from environment variables looked up with
+/// `var` and whether the output is a terminal.
+fn detect_with(var: impl Fn(&str) -> Option<String>, is_terminal: bool) -> Style {
+    // Empty values count as unset, as the NO_COLOR convention asks
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+    let dumb = var("TERM").as_deref() == Some("dumb");
+
+    let use_colors = if var("NO_COLOR").is_some() {
+        false
+    } else if var("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+        true
+    } else {
+        !dumb && var("CLICOLOR").as_deref() != Some("0") && is_terminal
+    };
+
+    let color_depth = if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit")) {
+        ColorDepth::TrueColor
+    } else if var("TERM").is_some_and(|term| term.contains("256color")) {
+        ColorDepth::Ansi256
+    } else {
+        ColorDepth::Ansi16
+    };
+
+    // Support for OSC 8 cannot be queried, so only known terminals get it
+    let known_terminal = matches!(
+        var("TERM_PROGRAM").as_deref(),
+        Some("iTerm.app" | "WezTerm" | "vscode" | "ghostty")
+    ) || var("WT_SESSION").is_some()
+        || var("KITTY_WINDOW_ID").is_some()
+        || var("VTE_VERSION").and_then(|v| v.parse::<u32>().ok()) >= Some(5000);
+    let hyperlinks = is_terminal && !dumb && known_terminal;
+
+    let locale = var("LC_ALL")
+        .or_else(|| var("LC_CTYPE"))
+        .or_else(|| var("LANG"));
+    let use_unicode_boxes = !dumb
+        && locale.map_or(cfg!(windows), |locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        });
+
+    Style::default()
+        .colors(use_colors)
+        .color_depth(color_depth)
+        .unicode_boxes(use_unicode_boxes)
+        .hyperlinks(hyperlinks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(vars: &[(&str, &str)], is_terminal: bool) -> Style {
+        detect_with(
+            |name| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            },
+            is_terminal,
+        )
+    }
+
+    fn detect(vars: &[(&str, &str)], is_terminal: bool) -> (bool, bool) {
+        let style = style(vars, is_terminal);
+        (style.use_colors, style.use_unicode_boxes)
+    }
+
+    #[test]
+    fn test_detect_colors() {
+        let utf8 = ("LANG", "en_US.UTF-8");
+        assert_eq!(detect(&[utf8], true), (true, true));
+        assert_eq!(detect(&[utf8], false), (false, true));
+        assert_eq!(detect(&[utf8, ("NO_COLOR", "1")], true), (false, true));
+        // An empty NO_COLOR is ignored
+        assert_eq!(detect(&[utf8, ("NO_COLOR", "")], true), (true, true));
+        assert!(!detect(&[("CLICOLOR", "0")], true).0);
+        assert!(detect(&[("CLICOLOR_FORCE", "1")], false).0);
+        assert!(!detect(&[("CLICOLOR_FORCE", "0")], false).0);
+        assert!(!detect(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")], true).0);
+        assert_eq!(detect(&[utf8, ("TERM", "dumb")], true), (false, false));
+    }
+
+    #[test]
+    fn test_detect_color_depth() {
+        let depth = |vars: &[(&str, &str)]| style(vars, true).color_depth;
+        assert_eq!(depth(&[("COLORTERM", "truecolor")]), ColorDepth::TrueColor);
+        assert_eq!(depth(&[("TERM", "xterm-256color")]), ColorDepth::Ansi256);
+        assert_eq!(depth(&[("TERM", "xterm")]), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn test_detect_hyperlinks() {
+        let hyperlinks = |vars: &[(&str, &str)], is_terminal| style(vars, is_terminal).hyperlinks;
+        assert!(hyperlinks(&[("TERM_PROGRAM", "WezTerm")], true));
+        assert!(hyperlinks(&[("VTE_VERSION", "6003")], true));
+        assert!(!hyperlinks(&[("VTE_VERSION", "4200")], true));
+        assert!(!hyperlinks(&[("TERM_PROGRAM", "WezTerm")], false));
+        assert!(!hyperlinks(&[("TERM_PROGRAM", "Apple_Terminal")], true));
+    }
+
+    #[test]
+    fn test_detect_locale() {
+        assert!(detect(&[("LC_ALL", "C.utf8")], false).1);
+        assert!(!detect(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")], false).1);
+        assert!(detect(&[("LC_ALL", ""), ("LC_CTYPE", "de_DE.UTF-8")], false).1);
+        assert!(!detect(&[("LANG", "POSIX")], false).1);
+    }
+}
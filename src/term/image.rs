@@ -0,0 +1,280 @@
+//! Terminal graphics for local image files.
+//!
+//! Only compiled with the `images` feature. Each encoder returns the full
+//! escape sequence text so the renderer can indent it like any other block.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, RgbaImage};
+
+use super::{Color, ColorDepth, ImageMode};
+
+/// Approximate pixel width of a terminal cell, used to size sixel output.
+const CELL_WIDTH: u32 = 8;
+/// Approximate pixel height of a terminal cell, used to size sixel output.
+const CELL_HEIGHT: u32 = 16;
+
+/// Load a local PNG or JPEG and encode it for `mode`.
+///
+/// Half-block pixels are downgraded to `depth`. Returns `None` when `url` is
+/// remote or the file cannot be decoded, so the caller can fall back to a
+/// placeholder.
+pub(super) fn load(url: &str, mode: ImageMode, width: usize, depth: ColorDepth) -> Option<String> {
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    if path.contains("://") {
+        return None;
+    }
+    let path = Path::new(path);
+    let format = ImageFormat::from_path(path).ok()?;
+    if !matches!(format, ImageFormat::Png | ImageFormat::Jpeg) {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    let decoded = image::load_from_memory_with_format(&bytes, format).ok()?;
+    let columns = (width.max(1) as u32).min(decoded.width().max(1));
+
+    match mode {
+        ImageMode::Placeholder => None,
+        ImageMode::HalfBlock => Some(half_block(&decoded, columns, depth)),
+        ImageMode::Kitty => {
+            // Kitty decodes PNG itself; other formats are re-encoded first
+            let png = if format == ImageFormat::Png {
+                bytes
+            } else {
+                let mut png = Vec::new();
+                decoded
+                    .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+                    .ok()?;
+                png
+            };
+            Some(kitty(&png, columns))
+        }
+        ImageMode::Sixel => Some(sixel(&decoded, columns)),
+    }
+}
+
+/// Scale `image` so it spans `width` pixels, preserving its aspect ratio.
+fn scale(image: &DynamicImage, width: u32) -> RgbaImage {
+    let height = (image.height() as f64 * width as f64 / image.width() as f64).round();
+    image
+        .resize_exact(width, (height as u32).max(1), FilterType::Triangle)
+        .to_rgba8()
+}
+
+/// Draw two pixel rows per line using `▀` with foreground and background colors.
+fn half_block(image: &DynamicImage, columns: u32, depth: ColorDepth) -> String {
+    let pixels = scale(image, columns);
+    let color = |p: [u8; 4]| Color::Rgb(p[0], p[1], p[2]).downgrade(depth);
+    let mut out = String::new();
+    for y in (0..pixels.height()).step_by(2) {
+        for x in 0..pixels.width() {
+            let top = pixels.get_pixel(x, y).0;
+            let bottom = (y + 1 < pixels.height()).then(|| pixels.get_pixel(x, y + 1).0);
+            let bottom = bottom.filter(|p| p[3] >= 128);
+            match (top[3] >= 128, bottom) {
+                (true, Some(b)) => write!(out, "{}{}▀", color(top).fg(), color(b).bg()),
+                (true, None) => write!(out, "\x1b[0m{}▀", color(top).fg()),
+                (false, Some(b)) => write!(out, "\x1b[0m{}▄", color(b).fg()),
+                (false, None) => write!(out, "\x1b[0m "),
+            }
+            .unwrap();
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// Transmit PNG data with the kitty graphics protocol in 4096-byte chunks.
+fn kitty(png: &[u8], columns: u32) -> String {
+    let encoded = base64(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        if i == 0 {
+            write!(out, "\x1b_Gf=100,a=T,c={columns},m={more};{chunk}\x1b\\").unwrap();
+        } else {
+            write!(out, "\x1b_Gm={more};{chunk}\x1b\\").unwrap();
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// Encode pixels as sixels using a fixed 6×6×6 color cube palette.
+fn sixel(image: &DynamicImage, columns: u32) -> String {
+    let pixels = scale(image, columns * CELL_WIDTH);
+    let (width, height) = pixels.dimensions();
+    let index = |x: u32, y: u32| -> Option<usize> {
+        let [r, g, b, a] = pixels.get_pixel(x, y).0;
+        let level = |c: u8| (c as usize * 5 + 127) / 255;
+        (a >= 128).then(|| level(r) * 36 + level(g) * 6 + level(b))
+    };
+
+    let mut out = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    for i in 0..216 {
+        let percent = |level: usize| level * 100 / 5;
+        write!(
+            out,
+            "#{i};2;{};{};{}",
+            percent(i / 36),
+            percent(i / 6 % 6),
+            percent(i % 6)
+        )
+        .unwrap();
+    }
+
+    for band in (0..height).step_by(6) {
+        let rows = (height - band).min(6);
+        let mut colors: Vec<usize> = (0..width)
+            .flat_map(|x| (0..rows).filter_map(move |dy| index(x, band + dy)))
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (n, color) in colors.iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            write!(out, "#{color}").unwrap();
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..width {
+                let bits = (0..rows)
+                    .filter(|&dy| index(x, band + dy) == Some(*color))
+                    .fold(0u8, |bits, dy| bits | (1 << dy));
+                let c = char::from(63 + bits);
+                run = match run {
+                    Some((prev, count)) if prev == c => Some((prev, count + 1)),
+                    Some((prev, count)) => {
+                        push_run(&mut out, prev, count);
+                        Some((c, 1))
+                    }
+                    None => Some((c, 1)),
+                };
+            }
+            if let Some((prev, count)) = run {
+                push_run(&mut out, prev, count);
+            }
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\\n");
+    // Leave room for the rows the graphic occupies
+    let lines = height.div_ceil(CELL_HEIGHT).saturating_sub(1);
+    out.push_str(&"\n".repeat(lines as usize));
+    out
+}
+
+fn push_run(out: &mut String, c: char, count: usize) {
+    if count > 3 {
+        write!(out, "!{count}{c}").unwrap();
+    } else {
+        out.extend(std::iter::repeat_n(c, count));
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Block;
+    use crate::term::{Renderer, Style};
+
+    fn sample_png(name: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        let image = RgbaImage::from_fn(4, 4, |x, _| {
+            if x < 2 {
+                image::Rgba([255, 0, 0, 255])
+            } else {
+                image::Rgba([0, 0, 255, 255])
+            }
+        });
+        image.save(&path).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_image_modes() {
+        let url = sample_png("docloom-image-modes.png");
+        let image = Block::Image {
+            alt: "Sample".into(),
+            url: url.clone(),
+        };
+
+        let style = Style::default().image_width(4);
+        let output =
+            Renderer::to_string_with_style(&image, style.clone().image_mode(ImageMode::HalfBlock));
+        assert!(output.contains("\x1b[38;2;255;0;0m\x1b[48;2;255;0;0m▀"));
+        assert!(!output.contains("Sample"));
+
+        // Pixels follow the terminal's color depth
+        let output = Renderer::to_string_with_style(
+            &image,
+            style
+                .clone()
+                .image_mode(ImageMode::HalfBlock)
+                .color_depth(ColorDepth::Ansi256),
+        );
+        assert!(output.contains("\x1b[38;5;196m\x1b[48;5;196m▀"));
+        assert!(!output.contains("38;2;"));
+
+        // Without colors every mode falls back to the placeholder
+        for mode in [ImageMode::HalfBlock, ImageMode::Kitty, ImageMode::Sixel] {
+            let output = Renderer::to_string_with_style(
+                &image,
+                Style::plain().image_width(4).image_mode(mode),
+            );
+            assert!(!output.contains('\x1b'));
+            assert!(output.contains("Sample"));
+        }
+
+        let output =
+            Renderer::to_string_with_style(&image, style.clone().image_mode(ImageMode::Kitty));
+        assert!(output.starts_with("\x1b_Gf=100,a=T,c=4,m=0;iVBORw0KGgo"));
+
+        let output =
+            Renderer::to_string_with_style(&image, style.clone().image_mode(ImageMode::Sixel));
+        assert!(output.starts_with("\x1bP0;1;0q\"1;1;32;32"));
+
+        // Missing files still render a placeholder
+        let output = Renderer::to_string_with_style(
+            &Block::Image {
+                alt: "Gone".into(),
+                url: format!("{url}.missing.png"),
+            },
+            style.image_mode(ImageMode::HalfBlock),
+        );
+        assert!(output.contains("Gone"));
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -0,0 +1,530 @@
+//! Color themes for the terminal renderer, built in or loaded from text.
+
+use std::{fmt, fs, io, path::Path, str::FromStr};
+
+use super::{Color, Style};
+use crate::{CalloutKind, highlight::TokenKind};
+
+/// The colors and decorations of terminal output, separate from layout.
+///
+/// Themes come built in ([`Theme::dark`], [`Theme::light`],
+/// [`Theme::solarized`], [`Theme::monochrome`]) or are parsed from a small
+/// TOML-like format, so users can restyle output without recompiling:
+///
+/// ```toml
+/// # Keys that are left out keep the colors of `base`, or of `dark`
+/// base = "solarized"
+/// headings = ["#268bd2", "cyan", 33]   # levels past the list reuse the last
+/// code = "bright-green"
+/// code_bg = "default"
+/// link = "blue"
+/// list = "yellow"
+/// border = "bright-black"
+/// table_header = "cyan"
+/// quote_border = "┃"
+/// keyword = "magenta"                  # and string, number, comment,
+/// comment = 245                        # property, inserted, deleted, meta
+/// warning = "#b58900"                  # and note, tip, important, danger
+/// ```
+///
+/// Colors are names such as `bright-cyan`, 256-color palette indices, or
+/// hex `#rrggbb` values; see [`Color::from_str`].
+///
+/// # Examples
+/// ```rust
+/// use docloom::prelude::*;
+/// use docloom::term::{Style, Theme, doc};
+///
+/// let theme: Theme = "base = \"light\"\nlink = \"#d33682\"".parse()?;
+/// let style = Style::default().theme(&theme);
+/// println!("{}", doc(p("Themed")).with_style(style));
+/// # Ok::<(), docloom::term::ThemeError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Theme {
+    /// Colors for heading levels 1 to 6.
+    pub heading_colors: [Color; 6],
+    /// Foreground color of code.
+    pub code_color: Color,
+    /// Background color of inline code.
+    pub code_bg: Color,
+    /// Color of hyperlinks.
+    pub link_color: Color,
+    /// Color of list markers.
+    pub list_color: Color,
+    /// Color of borders, rules and URLs.
+    pub border_color: Color,
+    /// Color of table header cells.
+    pub table_header_color: Color,
+    /// Character drawn left of quoted blocks, instead of `▎` or `|`.
+    pub quote_border: Option<char>,
+    /// Colors of highlighted code.
+    pub syntax: Syntax,
+    /// Colors of callout borders and titles.
+    pub callouts: Callouts,
+}
+
+/// Colors of highlighted code tokens; plain tokens use the code color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Syntax {
+    pub keyword: Color,
+    pub string: Color,
+    pub number: Color,
+    pub comment: Color,
+    pub property: Color,
+    pub inserted: Color,
+    pub deleted: Color,
+    pub meta: Color,
+}
+
+impl Syntax {
+    /// The color of tokens of `kind`, or `None` for plain tokens.
+    pub fn color(mut self, kind: TokenKind) -> Option<Color> {
+        self.color_mut(kind).copied()
+    }
+
+    fn color_mut(&mut self, kind: TokenKind) -> Option<&mut Color> {
+        match kind {
+            TokenKind::Plain => None,
+            TokenKind::Keyword => Some(&mut self.keyword),
+            TokenKind::String => Some(&mut self.string),
+            TokenKind::Number => Some(&mut self.number),
+            TokenKind::Comment => Some(&mut self.comment),
+            TokenKind::Property => Some(&mut self.property),
+            TokenKind::Inserted => Some(&mut self.inserted),
+            TokenKind::Deleted => Some(&mut self.deleted),
+            TokenKind::Meta => Some(&mut self.meta),
+        }
+    }
+}
+
+/// Colors of callouts, by kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Callouts {
+    pub note: Color,
+    pub tip: Color,
+    pub important: Color,
+    pub warning: Color,
+    pub danger: Color,
+}
+
+impl Callouts {
+    /// The color of callouts of `kind`.
+    pub fn color(mut self, kind: CalloutKind) -> Color {
+        *self.color_mut(kind)
+    }
+
+    fn color_mut(&mut self, kind: CalloutKind) -> &mut Color {
+        match kind {
+            CalloutKind::Note => &mut self.note,
+            CalloutKind::Tip => &mut self.tip,
+            CalloutKind::Important => &mut self.important,
+            CalloutKind::Warning => &mut self.warning,
+            CalloutKind::Danger => &mut self.danger,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// Bright colors for dark backgrounds, used by [`Style::default`].
+    pub fn dark() -> Self {
+        Self {
+            heading_colors: [
+                Color::BrightCyan,
+                Color::Cyan,
+                Color::BrightBlue,
+                Color::Blue,
+                Color::BrightWhite,
+                Color::BrightWhite,
+            ],
+            code_color: Color::Green,
+            code_bg: Color::Black,
+            link_color: Color::BrightBlue,
+            list_color: Color::BrightYellow,
+            border_color: Color::BrightBlack,
+            table_header_color: Color::BrightCyan,
+            quote_border: None,
+            syntax: Syntax {
+                keyword: Color::BrightMagenta,
+                string: Color::Yellow,
+                number: Color::BrightCyan,
+                comment: Color::BrightBlack,
+                property: Color::BrightBlue,
+                inserted: Color::BrightGreen,
+                deleted: Color::BrightRed,
+                meta: Color::Cyan,
+            },
+            callouts: Callouts {
+                note: Color::BrightBlue,
+                tip: Color::BrightGreen,
+                important: Color::BrightMagenta,
+                warning: Color::BrightYellow,
+                danger: Color::BrightRed,
+            },
+        }
+    }
+
+    /// Darker colors that stay readable on light backgrounds.
+    pub fn light() -> Self {
+        Self {
+            heading_colors: [
+                Color::Blue,
+                Color::Magenta,
+                Color::Blue,
+                Color::Magenta,
+                Color::Black,
+                Color::Black,
+            ],
+            code_color: Color::Red,
+            code_bg: Color::Default,
+            link_color: Color::Blue,
+            list_color: Color::Magenta,
+            border_color: Color::BrightBlack,
+            table_header_color: Color::Blue,
+            quote_border: None,
+            syntax: Syntax {
+                keyword: Color::Magenta,
+                string: Color::Green,
+                number: Color::Blue,
+                comment: Color::BrightBlack,
+                property: Color::Blue,
+                inserted: Color::Green,
+                deleted: Color::Red,
+                meta: Color::Cyan,
+            },
+            callouts: Callouts {
+                note: Color::Blue,
+                tip: Color::Green,
+                important: Color::Magenta,
+                warning: Color::Yellow,
+                danger: Color::Red,
+            },
+        }
+    }
+
+    /// The Solarized palette, in truecolor.
+    pub fn solarized() -> Self {
+        let blue = Color::Rgb(0x26, 0x8b, 0xd2);
+        let violet = Color::Rgb(0x6c, 0x71, 0xc4);
+        Self {
+            heading_colors: [
+                blue,
+                Color::Rgb(0x2a, 0xa1, 0x98),
+                Color::Rgb(0x85, 0x99, 0x00),
+                Color::Rgb(0xb5, 0x89, 0x00),
+                violet,
+                violet,
+            ],
+            code_color: Color::Rgb(0xcb, 0x4b, 0x16),
+            code_bg: Color::Rgb(0x07, 0x36, 0x42),
+            link_color: blue,
+            list_color: Color::Rgb(0xb5, 0x89, 0x00),
+            border_color: Color::Rgb(0x58, 0x6e, 0x75),
+            table_header_color: Color::Rgb(0x2a, 0xa1, 0x98),
+            quote_border: None,
+            syntax: Syntax {
+                keyword: Color::Rgb(0x85, 0x99, 0x00),
+                string: Color::Rgb(0x2a, 0xa1, 0x98),
+                number: Color::Rgb(0xd3, 0x36, 0x82),
+                comment: Color::Rgb(0x58, 0x6e, 0x75),
+                property: blue,
+                inserted: Color::Rgb(0x85, 0x99, 0x00),
+                deleted: Color::Rgb(0xdc, 0x32, 0x2f),
+                meta: violet,
+            },
+            callouts: Callouts {
+                note: blue,
+                tip: Color::Rgb(0x85, 0x99, 0x00),
+                important: violet,
+                warning: Color::Rgb(0xb5, 0x89, 0x00),
+                danger: Color::Rgb(0xdc, 0x32, 0x2f),
+            },
+        }
+    }
+
+    /// The terminal's default colors, leaving emphasis to bold and dim text.
+    pub fn monochrome() -> Self {
+        Self {
+            heading_colors: [Color::Default; 6],
+            code_color: Color::Default,
+            code_bg: Color::Default,
+            link_color: Color::Default,
+            list_color: Color::Default,
+            border_color: Color::Default,
+            table_header_color: Color::Default,
+            quote_border: None,
+            syntax: Syntax {
+                keyword: Color::Default,
+                string: Color::Default,
+                number: Color::Default,
+                comment: Color::Default,
+                property: Color::Default,
+                inserted: Color::Default,
+                deleted: Color::Default,
+                meta: Color::Default,
+            },
+            callouts: Callouts {
+                note: Color::Default,
+                tip: Color::Default,
+                important: Color::Default,
+                warning: Color::Default,
+                danger: Color::Default,
+            },
+        }
+    }
+
+    /// Look up a built-in theme by name, such as `"solarized"`.
+    pub fn named(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "solarized" => Some(Self::solarized()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Read and parse a theme file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ThemeError> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ThemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = Vec::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let syntax = |message: String| ThemeError::Syntax {
+                line: number + 1,
+                message,
+            };
+            let (key, value) = line
+                .split_once(['=', ':'])
+                .ok_or_else(|| syntax(format!("expected `key = value`, found `{line}`")))?;
+            let key = unquote(key.trim());
+            let value = value.trim().trim_end_matches(',').trim_end();
+            entries.push((number + 1, key, value));
+        }
+
+        // The base applies first, wherever it appears
+        let mut theme = Theme::dark();
+        for (line, key, value) in &entries {
+            if *key == "base" {
+                theme = Theme::named(unquote(value)).ok_or_else(|| ThemeError::Syntax {
+                    line: *line,
+                    message: format!("unknown theme `{}`", unquote(value)),
+                })?;
+            }
+        }
+
+        for (line, key, value) in entries {
+            let syntax = |message: String| ThemeError::Syntax { line, message };
+            let color = || {
+                unquote(value)
+                    .parse::<Color>()
+                    .map_err(|e| syntax(e.to_string()))
+            };
+            match key {
+                "base" => {}
+                "headings" => {
+                    let list = value
+                        .strip_prefix('[')
+                        .and_then(|v| v.strip_suffix(']'))
+                        .unwrap_or(value);
+                    let colors = list
+                        .split(',')
+                        .map(|item| unquote(item.trim()))
+                        .filter(|item| !item.is_empty())
+                        .map(|item| item.parse::<Color>().map_err(|e| syntax(e.to_string())))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let Some(last) = colors.last() else {
+                        return Err(syntax("`headings` needs at least one color".into()));
+                    };
+                    for (level, color) in theme.heading_colors.iter_mut().enumerate() {
+                        *color = *colors.get(level).unwrap_or(last);
+                    }
+                }
+                "code" => theme.code_color = color()?,
+                "code_bg" => theme.code_bg = color()?,
+                "link" => theme.link_color = color()?,
+                "list" => theme.list_color = color()?,
+                "border" => theme.border_color = color()?,
+                "table_header" => theme.table_header_color = color()?,
+                "quote_border" => {
+                    let mut chars = unquote(value).chars();
+                    theme.quote_border = match (chars.next(), chars.next()) {
+                        (Some(c), None) => Some(c),
+                        _ => return Err(syntax("`quote_border` must be one character".into())),
+                    };
+                }
+                key => {
+                    let kind = TokenKind::HIGHLIGHTED.into_iter().find(|k| k.name() == key);
+                    let callout = CalloutKind::ALL.into_iter().find(|k| k.name() == key);
+                    let slot = match (kind, callout) {
+                        (Some(kind), _) => theme.syntax.color_mut(kind),
+                        (_, Some(kind)) => Some(theme.callouts.color_mut(kind)),
+                        (None, None) => None,
+                    };
+                    *slot.ok_or_else(|| syntax(format!("unknown key `{key}`")))? = color()?;
+                }
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// Drop a `#` comment, keeping `#` that starts a hex color value.
+fn strip_comment(line: &str) -> &str {
+    let (mut quoted, mut listed) = (false, false);
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' | '\'' => quoted = !quoted,
+            '[' | ']' if !quoted => listed = c == '[',
+            '#' if !quoted => match previous {
+                '=' | ':' | '[' => {}
+                ',' if listed => {}
+                _ => return &line[..i],
+            },
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            previous = c;
+        }
+    }
+    line
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+/// Error returned when a [`Theme`] cannot be loaded.
+#[derive(Debug)]
+pub enum ThemeError {
+    /// The theme file could not be read.
+    Io(io::Error),
+    /// A line of the theme could not be understood.
+    Syntax {
+        /// One-based line number.
+        line: usize,
+        /// What was wrong with the line.
+        message: String,
+    },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(error) => write!(f, "could not read theme: {error}"),
+            ThemeError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::Io(error) => Some(error),
+            ThemeError::Syntax { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ThemeError {
+    fn from(error: io::Error) -> Self {
+        ThemeError::Io(error)
+    }
+}
+
+impl Style {
+    /// Use the colors and decorations of `theme`.
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        self.heading_colors = theme.heading_colors;
+        self.code_color = theme.code_color;
+        self.code_bg = theme.code_bg;
+        self.link_color = theme.link_color;
+        self.list_color = theme.list_color;
+        self.border_color = theme.border_color;
+        self.table_header_color = theme.table_header_color;
+        self.quote_border = theme.quote_border;
+        self.syntax = theme.syntax;
+        self.callouts = theme.callouts;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme() {
+        let theme: Theme = r##"
+            # Brand colors
+            base = "light"
+            headings = ["#ff8700", 33, 'bright-cyan']
+            link: "#d33682",   # JSON-ish separators work too
+            quote_border = "┃"
+            comment = 245
+            danger = "#ff0000"
+        "##
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            theme.heading_colors,
+            [
+                Color::Rgb(255, 135, 0),
+                Color::Ansi256(33),
+                Color::BrightCyan,
+                Color::BrightCyan,
+                Color::BrightCyan,
+                Color::BrightCyan,
+            ]
+        );
+        assert_eq!(theme.link_color, Color::Rgb(0xd3, 0x36, 0x82));
+        assert_eq!(theme.quote_border, Some('┃'));
+        assert_eq!(theme.syntax.comment, Color::Ansi256(245));
+        assert_eq!(theme.callouts.danger, Color::Rgb(255, 0, 0));
+        // Unset keys come from the base theme
+        assert_eq!(theme.code_color, Theme::light().code_color);
+        assert_eq!("".parse::<Theme>().unwrap(), Theme::dark());
+    }
+
+    #[test]
+    fn test_theme_errors() {
+        let error = |text: &str| text.parse::<Theme>().unwrap_err().to_string();
+        assert_eq!(error("link = \"#12\""), "line 1: invalid color `#12`");
+        assert_eq!(error("\nbase = neon"), "line 2: unknown theme `neon`");
+        assert_eq!(error("links = blue"), "line 1: unknown key `links`");
+        assert_eq!(
+            error("headings = []"),
+            "line 1: `headings` needs at least one color"
+        );
+        assert_eq!(
+            error("cyan"),
+            "line 1: expected `key = value`, found `cyan`"
+        );
+        assert!(matches!(
+            Theme::load("/nonexistent/theme.toml"),
+            Err(ThemeError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,235 @@
+//! ANSI-aware soft wrapping for rendered terminal text.
+//!
+//! Escape sequences are zero-width. SGR attributes and OSC 8 hyperlinks that
+//! are active at a break are closed at the end of the line and reopened at
+//! the start of the next, so indentation never inherits backgrounds or
+//! underlines.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::width;
+
+/// A piece of rendered text: an escape sequence or a visible grapheme.
+enum Piece<'a> {
+    Escape(&'a str),
+    Grapheme(&'a str),
+}
+
+/// Split `text` into escape sequences and visible grapheme clusters.
+fn pieces(text: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let plain = rest.find('\x1b').unwrap_or(rest.len());
+        pieces.extend(rest[..plain].graphemes(true).map(Piece::Grapheme));
+        rest = &rest[plain..];
+        if !rest.is_empty() {
+            let len = escape_len(rest);
+            pieces.push(Piece::Escape(&rest[..len]));
+            rest = &rest[len..];
+        }
+    }
+    pieces
+}
+
+/// Byte length of the CSI or OSC escape sequence at the start of `text`.
+fn escape_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    match bytes.get(1) {
+        // CSI: parameters followed by a final byte in `@..=~`
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(text.len(), |end| end + 3),
+        // OSC: terminated by BEL or ST (`ESC \`)
+        Some(b']') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    0x07 => return i + 1,
+                    0x1b if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            text.len()
+        }
+        _ => 1,
+    }
+}
+
+/// Display width of `text`, ignoring escape sequences.
+pub(super) fn visible_width(text: &str) -> usize {
+    pieces(text)
+        .iter()
+        .map(|piece| match piece {
+            Piece::Escape(_) => 0,
+            Piece::Grapheme(g) => width::width(g),
+        })
+        .sum()
+}
+
+/// Styling that is active at some point in a line.
+#[derive(Default, Clone)]
+struct State {
+    sgr: String,
+    link: Option<String>,
+}
+
+impl State {
+    fn apply(&mut self, escape: &str) {
+        if escape == "\x1b[0m" || escape == "\x1b[m" {
+            self.sgr.clear();
+        } else if escape.starts_with("\x1b[") && escape.ends_with('m') {
+            self.sgr.push_str(escape);
+        } else if let Some(params) = escape.strip_prefix("\x1b]8;") {
+            let url = params.split_once(';').map_or("", |(_, rest)| rest);
+            let url = url
+                .trim_end_matches(['\x07', '\\'])
+                .trim_end_matches('\x1b');
+            self.link = (!url.is_empty()).then(|| escape.to_string());
+        }
+    }
+
+    /// Sequences that end the active styling before a line break.
+    fn close(&self) -> String {
+        let mut out = String::new();
+        if self.link.is_some() {
+            out.push_str("\x1b]8;;\x1b\\");
+        }
+        if !self.sgr.is_empty() {
+            out.push_str("\x1b[0m");
+        }
+        out
+    }
+
+    /// Sequences that restore the active styling after a line break.
+    fn reopen(&self) -> String {
+        let mut out = self.sgr.clone();
+        if let Some(link) = &self.link {
+            out.push_str(link);
+        }
+        out
+    }
+}
+
+/// A run of non-space pieces together with its display width.
+#[derive(Default)]
+struct Word<'a> {
+    pieces: Vec<Piece<'a>>,
+    width: usize,
+}
+
+/// Wrap `text` at word boundaries so no line exceeds `width` columns.
+///
+/// Existing newlines are kept as hard breaks; words longer than `width` are
+/// split across lines.
+pub(super) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut state = State::default();
+
+    for hard_line in text.split('\n') {
+        let mut line = state.reopen();
+        let mut line_width = 0;
+        let mut spaces = 0;
+        let mut at_start = true;
+
+        for (gap, word) in words(hard_line) {
+            spaces += gap;
+            let fits = line_width + spaces + word.width <= width;
+            if !at_start && !fits && word.width > 0 {
+                line.push_str(&state.close());
+                lines.push(line);
+                line = state.reopen();
+                line_width = 0;
+            } else {
+                line.extend(std::iter::repeat_n(' ', spaces));
+                line_width += spaces;
+            }
+            spaces = 0;
+            at_start = false;
+
+            for piece in word.pieces {
+                match piece {
+                    Piece::Escape(escape) => {
+                        state.apply(escape);
+                        line.push_str(escape);
+                    }
+                    Piece::Grapheme(g) => {
+                        let w = width::width(g);
+                        // Split words that cannot fit on a line of their own
+                        if line_width + w > width && line_width > 0 {
+                            line.push_str(&state.close());
+                            lines.push(line);
+                            line = state.reopen();
+                            line_width = 0;
+                        }
+                        line.push_str(g);
+                        line_width += w;
+                    }
+                }
+            }
+        }
+        // Trailing spaces are dropped rather than wrapped onto a new line
+        lines.push(line);
+    }
+    lines
+}
+
+/// Group pieces into words, each paired with the spaces preceding it.
+fn words(text: &str) -> Vec<(usize, Word<'_>)> {
+    let mut words = Vec::new();
+    let mut gap = 0;
+    let mut word = Word::default();
+    for piece in pieces(text) {
+        match piece {
+            Piece::Grapheme(" ") => {
+                if word.width > 0 {
+                    words.push((gap, std::mem::take(&mut word)));
+                    gap = 0;
+                }
+                gap += 1;
+            }
+            Piece::Grapheme(g) => {
+                word.width += width::width(g);
+                word.pieces.push(piece);
+            }
+            Piece::Escape(_) => word.pieces.push(piece),
+        }
+    }
+    if !word.pieces.is_empty() {
+        words.push((gap, word));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_words() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 10),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("one\ntwo three", 5), vec!["one", "two", "three"]);
+        // Wide characters count as two columns
+        assert_eq!(wrap("日本語 テキスト", 8), vec!["日本語", "テキスト"]);
+    }
+
+    #[test]
+    fn test_wrap_escapes() {
+        let lines = wrap("plain \x1b[1mbold words\x1b[0m end", 10);
+        assert_eq!(
+            lines,
+            vec!["plain \x1b[1mbold\x1b[0m", "\x1b[1mwords\x1b[0m end"]
+        );
+        assert_eq!(visible_width(&lines[1]), 9);
+
+        let lines = wrap("\x1b]8;;https://a.b\x1b\\link text\x1b]8;;\x1b\\", 5);
+        assert_eq!(lines[0], "\x1b]8;;https://a.b\x1b\\link\x1b]8;;\x1b\\");
+        assert_eq!(lines[1], "\x1b]8;;https://a.b\x1b\\text\x1b]8;;\x1b\\");
+    }
+}
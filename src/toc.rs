@@ -0,0 +1,278 @@
+//! Table of contents generation from document headings.
+
+use crate::slug::Slugger;
+use crate::{Block, Inline, Visitor, plain_text, walk_block};
+
+/// Where a document inserts its table of contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TocPosition {
+    /// Before all other content.
+    Start,
+    /// After the first level 1 heading, which is left out of the contents.
+    /// Falls back to [`TocPosition::Start`] when there is no such heading.
+    AfterTitle,
+    /// Before the top-level block at this index.
+    Index(usize),
+}
+
+/// Options for building a table of contents.
+///
+/// # Examples
+/// ```rust
+/// use docloom::prelude::*;
+/// use docloom::{Toc, TocPosition, md};
+///
+/// let markdown = md::doc([h1("Guide"), h2("Install"), h3("From source"), h2("Usage")])
+///     .with_toc(Toc::new().position(TocPosition::AfterTitle).levels(2, 2))
+///     .to_string();
+/// assert!(markdown.contains("- [Install](#install)\n- [Usage](#usage)\n"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Toc {
+    position: TocPosition,
+    min_level: u8,
+    max_level: u8,
+    links: bool,
+}
+
+impl Default for Toc {
+    fn default() -> Self {
+        Self {
+            position: TocPosition::Start,
+            min_level: 1,
+            max_level: 6,
+            links: true,
+        }
+    }
+}
+
+impl From<TocPosition> for Toc {
+    fn from(position: TocPosition) -> Self {
+        Toc::new().position(position)
+    }
+}
+
+impl Toc {
+    /// Create options that include every heading level, linked to anchors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set where [`Toc::insert`] places the contents.
+    pub fn position(mut self, position: TocPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Only include headings from level `min` to `max`, inclusive.
+    pub fn levels(mut self, min: u8, max: u8) -> Self {
+        self.min_level = min;
+        self.max_level = max;
+        self
+    }
+
+    /// Link entries to GitHub-compatible heading anchors, or list plain text.
+    pub fn links(mut self, links: bool) -> Self {
+        self.links = links;
+        self
+    }
+
+    /// Build a nested list of the headings in `blocks`.
+    ///
+    /// A heading that skips levels is nested directly under the closest
+    /// preceding heading of a lower level.
+    pub fn build(&self, blocks: &[Block]) -> Block {
+        self.build_after(blocks, 0)
+    }
+
+    /// Build the contents, leaving out the first `skip` headings.
+    fn build_after(&self, blocks: &[Block], skip: usize) -> Block {
+        let mut slugger = Slugger::default();
+        let entries: Vec<(u8, Block)> = headings(blocks)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (level, text, id))| {
+                // Explicit ids win, but the text still claims its slug and
+                // later slugs are numbered around the id
+                let slug = slugger.slug(&text);
+                if let Some(id) = &id {
+                    slugger.reserve(id);
+                }
+                let anchor = id.unwrap_or(slug);
+                if i < skip || !(self.min_level..=self.max_level).contains(&level) {
+                    return None;
+                }
+                let entry = if self.links {
+                    Inline::Link {
+                        text: vec![Inline::Text(text)],
+                        url: format!("#{anchor}"),
+                    }
+                } else {
+                    Inline::Text(text)
+                };
+                Some((level, Block::Paragraph(vec![entry])))
+            })
+            .collect();
+        Block::List {
+            ordered: false,
+            items: nest(entries),
+        }
+    }
+
+    /// Return `blocks` with the table of contents inserted at the configured
+    /// position. Nothing is inserted when no headings match.
+    pub fn insert(&self, blocks: &[Block]) -> Vec<Block> {
+        let at = match self.position {
+            TocPosition::Start => 0,
+            TocPosition::AfterTitle => blocks
+                .iter()
+                .position(|b| matches!(b, Block::Heading { level: 1, .. }))
+                .map_or(0, |i| i + 1),
+            TocPosition::Index(i) => i.min(blocks.len()),
+        };
+        // The title still takes its slug so later duplicates are numbered
+        let skip = match self.position {
+            TocPosition::AfterTitle => headings(&blocks[..at]).len(),
+            _ => 0,
+        };
+        let mut out = blocks.to_vec();
+        let toc = self.build_after(blocks, skip);
+        if !matches!(&toc, Block::List { items, .. } if items.is_empty()) {
+            out.insert(at, toc);
+        }
+        out
+    }
+}
+
+/// Build a table of contents of every heading in `blocks`.
+pub fn toc(blocks: &[Block]) -> Block {
+    Toc::new().build(blocks)
+}
+
+/// Levels, text and explicit ids of every heading in document order,
+/// including nested ones.
+fn headings(blocks: &[Block]) -> Vec<(u8, String, Option<String>)> {
+    struct Headings(Vec<(u8, String, Option<String>)>);
+    impl Visitor for Headings {
+        fn visit_block(&mut self, block: &Block) {
+            if let Block::Heading { level, content, id } = block {
+                self.0.push((*level, plain_text(content), id.clone()));
+            }
+            walk_block(self, block);
+        }
+    }
+
+    let mut headings = Headings(Vec::new());
+    blocks.iter().for_each(|b| headings.visit_block(b));
+    headings.0
+}
+
+/// Group entries under the closest preceding entry of a lower level.
+fn nest(entries: Vec<(u8, Block)>) -> Vec<Block> {
+    let mut items = Vec::new();
+    let mut entries = entries.into_iter().peekable();
+    while let Some((level, entry)) = entries.next() {
+        let mut children = Vec::new();
+        while let Some(child) = entries.next_if(|(l, _)| *l > level) {
+            children.push(child);
+        }
+        items.push(if children.is_empty() {
+            entry
+        } else {
+            Block::BlockList(vec![
+                entry,
+                Block::List {
+                    ordered: false,
+                    items: nest(children),
+                },
+            ])
+        });
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_toc_nesting() {
+        let blocks = [
+            h1("Guide"),
+            p("Intro"),
+            h2("Setup"),
+            h4("Deep"),
+            h3("Details"),
+            quote(h2("Setup")),
+            h2(vec![code("toc()")]),
+        ];
+        assert_eq!(
+            toc(&blocks),
+            ul(Block::BlockList(vec![
+                p(vec![link("Guide", "#guide")]),
+                ul((
+                    Block::BlockList(vec![
+                        p(vec![link("Setup", "#setup")]),
+                        ul((
+                            p(vec![link("Deep", "#deep")]),
+                            p(vec![link("Details", "#details")])
+                        )),
+                    ]),
+                    p(vec![link("Setup", "#setup-1")]),
+                    p(vec![link("toc()", "#toc")]),
+                )),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_toc_insert() {
+        let blocks = [h1("Title"), p("Intro"), h2("One"), h3("Sub"), h2("Title")];
+        let outline = Toc::new()
+            .position(TocPosition::AfterTitle)
+            .levels(2, 2)
+            .links(false);
+        assert_eq!(
+            outline.insert(&blocks),
+            [
+                h1("Title"),
+                ul(["One", "Title"]),
+                p("Intro"),
+                h2("One"),
+                h3("Sub"),
+                h2("Title")
+            ]
+        );
+
+        // Anchors account for the title even though it is not listed
+        let linked = Toc::from(TocPosition::AfterTitle).insert(&blocks);
+        assert!(
+            matches!(&linked[1], Block::List { items, .. } if items[1] == p(vec![link("Title", "#title-1")]))
+        );
+
+        // Explicit ids replace the derived anchor and are not reused
+        assert_eq!(
+            toc(&[h2("A").with_id("setup"), h2("Setup")]),
+            ul([
+                p(vec![link("A", "#setup")]),
+                p(vec![link("Setup", "#setup-1")])
+            ])
+        );
+        let blocks = [h2("Setup").with_id("install"), h2("Setup")];
+        assert_eq!(
+            toc(&blocks),
+            ul([
+                p(vec![link("Setup", "#install")]),
+                p(vec![link("Setup", "#setup-1")])
+            ])
+        );
+
+        // Documents without matching headings are left alone
+        assert_eq!(Toc::new().levels(4, 6).insert(&blocks), blocks);
+        assert_eq!(
+            Toc::from(TocPosition::Index(9)).insert(&[p("x")])[0],
+            p("x")
+        );
+    }
+}
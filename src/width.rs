@@ -0,0 +1,63 @@
+//! Display width measurement shared by the renderers.
+//!
+//! Widths are counted per grapheme cluster, so combining marks, zero-width
+//! joiner sequences and emoji modifiers occupy the columns of the glyph they
+//! form, and East Asian wide characters count as two columns.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::Alignment;
+
+/// Display width of `text` in terminal columns.
+pub(crate) fn width(text: &str) -> usize {
+    text.graphemes(true).map(UnicodeWidthStr::width).sum()
+}
+
+/// Pad `text` with spaces to `width` columns according to `align`.
+///
+/// Text that is already at least `width` columns wide is returned unchanged.
+pub(crate) fn pad(text: &str, width: usize, align: Alignment) -> String {
+    pad_measured(text, self::width(text), width, align)
+}
+
+/// Like [`pad`], for `text` whose display width was measured by the caller,
+/// such as text containing escape sequences.
+pub(crate) fn pad_measured(
+    text: &str,
+    text_width: usize,
+    width: usize,
+    align: Alignment,
+) -> String {
+    let padding = width.saturating_sub(text_width);
+    let (left, right) = match align {
+        Alignment::Left => (0, padding),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+        Alignment::Right => (padding, 0),
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_width() {
+        assert_eq!(width("table"), 5);
+        assert_eq!(width("日本語"), 6);
+        assert_eq!(width("e\u{301}te\u{301}"), 3);
+        assert_eq!(width("👨\u{200d}👩\u{200d}👧"), 2);
+        assert_eq!(width("👍🏽 ok"), 5);
+        assert_eq!(width("🇬🇧"), 2);
+        assert_eq!(width("a\u{200b}b"), 2);
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad("日本", 6, Alignment::Left), "日本  ");
+        assert_eq!(pad("日本", 7, Alignment::Center), " 日本  ");
+        assert_eq!(pad("e\u{301}", 3, Alignment::Right), "  e\u{301}");
+        assert_eq!(pad("wide", 2, Alignment::Left), "wide");
+    }
+}